@@ -0,0 +1,187 @@
+package main
+
+import (
+	"strings"
+
+	"github.com/apache/arrow/go/v14/arrow"
+	"github.com/apache/arrow/go/v14/arrow/array"
+	"github.com/apache/arrow/go/v14/arrow/memory"
+)
+
+//
+// ---------------- ARROW ----------------
+//
+
+func (f DataItem) ArrowType() arrow.DataType {
+	return arrowType(f.Type)
+}
+
+func arrowType(t string) arrow.DataType {
+	if elem, ok := listElem(t); ok {
+		return arrow.ListOf(arrowType(elem))
+	}
+	switch t {
+	case "bool":
+		return arrow.FixedWidthTypes.Boolean
+	case "int8":
+		return arrow.PrimitiveTypes.Int8
+	case "int16":
+		return arrow.PrimitiveTypes.Int16
+	case "int32":
+		return arrow.PrimitiveTypes.Int32
+	case "int64":
+		return arrow.PrimitiveTypes.Int64
+	case "float32":
+		return arrow.PrimitiveTypes.Float32
+	case "float64":
+		return arrow.PrimitiveTypes.Float64
+	case "uint8":
+		return arrow.PrimitiveTypes.Uint8
+	case "uint16":
+		return arrow.PrimitiveTypes.Uint16
+	case "uint32":
+		return arrow.PrimitiveTypes.Uint32
+	case "uint64":
+		return arrow.PrimitiveTypes.Uint64
+	case "timestamp":
+		return &arrow.TimestampType{Unit: arrow.Microsecond, TimeZone: "UTC"}
+	case "dict", "enum":
+		return &arrow.DictionaryType{IndexType: arrow.PrimitiveTypes.Int32, ValueType: arrow.BinaryTypes.String}
+	default:
+		return arrow.BinaryTypes.String
+	}
+}
+
+// validType diz se type é conhecido. ArrowType transforma qualquer type
+// desconhecido em string, e o erro passaria despercebido.
+func validType(t string) bool {
+	if elem, ok := listElem(t); ok {
+		return validType(elem)
+	}
+	return t == "" || t == "string" || DataItem{Type: t}.ArrowType().ID() != arrow.STRING
+}
+
+// listElem retorna o tipo dos elementos de "list<T>".
+func listElem(t string) (string, bool) {
+	if strings.HasPrefix(t, "list<") && strings.HasSuffix(t, ">") {
+		return t[5 : len(t)-1], true
+	}
+	return "", false
+}
+
+// arrowSchema monta o schema das colunas, com a unidade nos metadados.
+func arrowSchema(fields []DataItem) *arrow.Schema {
+	arrowFields := make([]arrow.Field, len(fields))
+	for i, f := range fields {
+		arrowFields[i] = arrow.Field{
+			Name:     f.Label,
+			Type:     f.ArrowType(),
+			Nullable: true,
+		}
+		if f.Unit != "" {
+			arrowFields[i].Metadata = arrow.NewMetadata([]string{"unit"}, []string{f.Unit})
+		}
+	}
+	return arrow.NewSchema(arrowFields, nil)
+}
+
+// rowBatch acumula linhas nos builders Arrow até completar um lote. É a
+// base dos sinks parquet e arrow.
+type rowBatch struct {
+	schema   *arrow.Schema
+	builders []array.Builder
+	mem      memory.Allocator
+	rows     int
+	size     int
+}
+
+func newRowBatch(fields []DataItem, size int) *rowBatch {
+	b := &rowBatch{
+		schema:   arrowSchema(fields),
+		builders: make([]array.Builder, len(fields)),
+		mem:      memory.NewGoAllocator(),
+		size:     size,
+	}
+	for i, f := range b.schema.Fields() {
+		b.builders[i] = array.NewBuilder(b.mem, f.Type)
+	}
+	return b
+}
+
+// append adiciona a linha e diz se o lote está completo.
+func (b *rowBatch) append(values []any) bool {
+	for i, v := range values {
+		appendValue(b.builders[i], v)
+	}
+	b.rows++
+	return b.rows >= b.size
+}
+
+// record fecha o lote atual; quem chama libera o record.
+func (b *rowBatch) record() arrow.Record {
+	arrays := make([]arrow.Array, len(b.builders))
+	for i, bd := range b.builders {
+		arrays[i] = bd.NewArray()
+	}
+	record := array.NewRecord(b.schema, arrays, int64(b.rows))
+	for _, a := range arrays {
+		a.Release()
+	}
+
+	// recria os builders
+	for i, f := range b.schema.Fields() {
+		b.builders[i].Release()
+		b.builders[i] = array.NewBuilder(b.mem, f.Type)
+	}
+
+	b.rows = 0
+	return record
+}
+
+// appendValue recebe valores já convertidos por Converter (nil = null).
+func appendValue(b array.Builder, v any) {
+	if v == nil {
+		b.AppendNull()
+		return
+	}
+
+	switch bb := b.(type) {
+	case *array.BooleanBuilder:
+		bb.Append(v.(bool))
+	case *array.Int8Builder:
+		bb.Append(v.(int8))
+	case *array.Int16Builder:
+		bb.Append(v.(int16))
+	case *array.Int32Builder:
+		bb.Append(v.(int32))
+	case *array.Int64Builder:
+		bb.Append(v.(int64))
+	case *array.Float32Builder:
+		bb.Append(v.(float32))
+	case *array.Float64Builder:
+		bb.Append(v.(float64))
+	case *array.StringBuilder:
+		bb.Append(v.(string))
+	case *array.Uint8Builder:
+		bb.Append(v.(uint8))
+	case *array.Uint16Builder:
+		bb.Append(v.(uint16))
+	case *array.Uint32Builder:
+		bb.Append(v.(uint32))
+	case *array.Uint64Builder:
+		bb.Append(v.(uint64))
+	case *array.TimestampBuilder:
+		bb.Append(v.(arrow.Timestamp))
+	case *array.BinaryDictionaryBuilder:
+		if err := bb.AppendString(v.(string)); err != nil {
+			bb.AppendNull()
+		}
+	case *array.ListBuilder:
+		bb.Append(true)
+		for _, e := range v.([]any) {
+			appendValue(bb.ValueBuilder(), e)
+		}
+	default:
+		b.AppendNull()
+	}
+}
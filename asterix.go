@@ -0,0 +1,359 @@
+package main
+
+import (
+	"encoding/binary"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"strconv"
+	"strings"
+)
+
+//
+// ---------------- ASTERIX ----------------
+//
+
+// tipos de elemento
+const (
+	KindRaw    = "raw"    // inteiro sem sinal
+	KindSigned = "signed" // inteiro com sinal (complemento de 2)
+	KindHex    = "hex"    // inteiro em hexadecimal (0x3c6589), como o tshark mostra endereços
+	KindICAO6  = "icao6"  // caracteres ICAO de 6 bits
+	KindASCII  = "ascii"  // caracteres de 8 bits
+	KindOctal  = "octal"  // códigos modo 1/2/3A
+	KindBytes  = "bytes"  // conteúdo opaco (SP/RE)
+	KindSpare  = "spare"  // bits reservados, não geram campo
+)
+
+// formatos de item
+const (
+	FormatFixed      = "fixed"
+	FormatExtended   = "extended"
+	FormatRepetitive = "repetitive"
+	FormatCompound   = "compound"
+	FormatExplicit   = "explicit"
+)
+
+// Element é um campo de bits dentro de um item.
+type Element struct {
+	Name string  `json:"name,omitempty"`
+	Bits int     `json:"bits"`
+	Kind string  `json:"kind"`
+	LSB  float64 `json:"lsb,omitempty"` // 0 = valor inteiro, sem escala
+	Unit string  `json:"unit,omitempty"`
+}
+
+// Item descreve o layout de um data item (ou subitem de um compound).
+type Item struct {
+	ID       string      `json:"id"`
+	Name     string      `json:"name,omitempty"`
+	Format   string      `json:"format"`
+	Elements []Element   `json:"elements,omitempty"` // fixed, repetitive e explicit
+	Groups   [][]Element `json:"groups,omitempty"`   // extended: um grupo por octeto(s), sem o FX
+	Subitems []*Item     `json:"subitems,omitempty"` // compound: ordem do FSPEC, nil para spare
+}
+
+// Category é a UAP de uma categoria/edição.
+type Category struct {
+	Number  int
+	Edition string
+	UAP     []string // ID do item por FRN (índice 0 = FRN 1), "" para spare
+	Items   map[string]*Item
+}
+
+// Record é um registro ASTERIX decodificado.
+// As chaves seguem os nomes de campo do dissector do wireshark
+// (asterix.021_080_VALUE, asterix.021_090_NACP, ...).
+type Record struct {
+	Category int
+	Fields   map[string]string
+}
+
+// SacSic retorna o identificador da fonte (item 010) do registro.
+func (r Record) SacSic() (sac, sic int, ok bool) {
+	prefix := fmt.Sprintf("asterix.%03d_010_", r.Category)
+	sac, err1 := strconv.Atoi(r.Fields[prefix+"SAC"])
+	sic, err2 := strconv.Atoi(r.Fields[prefix+"SIC"])
+	return sac, sic, err1 == nil && err2 == nil
+}
+
+// Packet é um quadro da captura com os registros ASTERIX que ele carrega.
+// O backend nativo entrega o Payload ainda não decodificado; o tshark
+// entrega os Records prontos.
+type Packet struct {
+	Fields  map[string]string // campos de quadro (frame.time_epoch, ip.src, ...)
+	Payload []byte
+	Records []Record
+}
+
+type AsterixDecoder struct {
+	cats map[int]*Category
+}
+
+func NewAsterixDecoder(cats ...*Category) *AsterixDecoder {
+	d := &AsterixDecoder{cats: map[int]*Category{}}
+	for _, c := range cats {
+		d.cats[c.Number] = c
+	}
+	return d
+}
+
+// Decode percorre todos os data blocks de um datagrama. Os registros
+// decodificados antes de um erro são retornados junto com o erro.
+func (d *AsterixDecoder) Decode(data []byte) ([]Record, error) {
+	var records []Record
+	for len(data) > 0 {
+		if len(data) < 3 {
+			return records, fmt.Errorf("data block truncado (%d bytes)", len(data))
+		}
+		cat := int(data[0])
+		length := int(binary.BigEndian.Uint16(data[1:3]))
+		if length < 3 || length > len(data) {
+			return records, fmt.Errorf("CAT%03d: tamanho de bloco inválido %d", cat, length)
+		}
+		block := data[3:length]
+		data = data[length:]
+
+		c, ok := d.cats[cat]
+		if !ok {
+			// categoria sem UAP: ignora o bloco inteiro
+			continue
+		}
+		for len(block) > 0 {
+			rec, n, err := c.decodeRecord(block)
+			if err != nil {
+				return records, err
+			}
+			records = append(records, rec)
+			block = block[n:]
+		}
+	}
+	return records, nil
+}
+
+func (c *Category) decodeRecord(data []byte) (Record, int, error) {
+	rec := Record{Category: c.Number, Fields: map[string]string{}}
+
+	fspec, n, err := readFSPEC(data)
+	if err != nil {
+		return rec, 0, fmt.Errorf("CAT%03d: %w", c.Number, err)
+	}
+	pos := n
+
+	for frn := range fspec {
+		if !fspec[frn] {
+			continue
+		}
+		if frn >= len(c.UAP) || c.UAP[frn] == "" {
+			return rec, 0, fmt.Errorf("CAT%03d: FRN %d sem item na UAP", c.Number, frn+1)
+		}
+		item, ok := c.Items[c.UAP[frn]]
+		if !ok {
+			return rec, 0, fmt.Errorf("CAT%03d: item %s não definido", c.Number, c.UAP[frn])
+		}
+		prefix := fmt.Sprintf("asterix.%03d_%s", c.Number, item.ID)
+		size, err := item.decode(data[pos:], prefix, rec.Fields)
+		if err != nil {
+			return rec, 0, fmt.Errorf("CAT%03d/%s: %w", c.Number, item.ID, err)
+		}
+		pos += size
+	}
+	return rec, pos, nil
+}
+
+// readFSPEC retorna a presença de cada FRN/subcampo e o número de octetos lidos.
+func readFSPEC(data []byte) ([]bool, int, error) {
+	var present []bool
+	for i := 0; ; i++ {
+		if i >= len(data) {
+			return nil, 0, fmt.Errorf("FSPEC truncado")
+		}
+		b := data[i]
+		for bit := 7; bit >= 1; bit-- {
+			present = append(present, b&(1<<bit) != 0)
+		}
+		if b&1 == 0 {
+			return present, i + 1, nil
+		}
+	}
+}
+
+func (it *Item) decode(data []byte, prefix string, out map[string]string) (int, error) {
+	switch it.Format {
+	case FormatFixed:
+		size := elementsSize(it.Elements)
+		if len(data) < size {
+			return 0, errTruncated
+		}
+		decodeElements(data[:size], it.Elements, prefix, out)
+		return size, nil
+
+	case FormatExtended:
+		pos := 0
+		for g := 0; ; g++ {
+			var elems []Element
+			size := 1
+			if g < len(it.Groups) {
+				elems = it.Groups[g]
+				size = (elementsBits(elems) + 1) / 8
+			}
+			if len(data) < pos+size {
+				return 0, errTruncated
+			}
+			chunk := data[pos : pos+size]
+			decodeElements(chunk, elems, prefix, out)
+			pos += size
+			if chunk[size-1]&1 == 0 {
+				return pos, nil
+			}
+		}
+
+	case FormatRepetitive:
+		if len(data) < 1 {
+			return 0, errTruncated
+		}
+		rep := int(data[0])
+		size := elementsSize(it.Elements)
+		if len(data) < 1+rep*size {
+			return 0, errTruncated
+		}
+		for i := 0; i < rep; i++ {
+			off := 1 + i*size
+			decodeElements(data[off:off+size], it.Elements, prefix, out)
+		}
+		return 1 + rep*size, nil
+
+	case FormatCompound:
+		fspec, pos, err := readFSPEC(data)
+		if err != nil {
+			return 0, err
+		}
+		for i := range fspec {
+			if !fspec[i] {
+				continue
+			}
+			if i >= len(it.Subitems) || it.Subitems[i] == nil {
+				return 0, fmt.Errorf("subcampo %d não definido", i+1)
+			}
+			sub := it.Subitems[i]
+			n, err := sub.decode(data[pos:], prefix+"_"+sub.ID, out)
+			if err != nil {
+				return 0, err
+			}
+			pos += n
+		}
+		return pos, nil
+
+	case FormatExplicit:
+		if len(data) < 1 || int(data[0]) < 1 || len(data) < int(data[0]) {
+			return 0, errTruncated
+		}
+		size := int(data[0])
+		body := data[1:size]
+		if len(it.Elements) == 0 {
+			appendField(out, prefix+"_VALUE", hex.EncodeToString(body))
+		} else if n := elementsSize(it.Elements); n > 0 {
+			for off := 0; off+n <= len(body); off += n {
+				decodeElements(body[off:off+n], it.Elements, prefix, out)
+			}
+		}
+		return size, nil
+	}
+	return 0, fmt.Errorf("formato desconhecido %q", it.Format)
+}
+
+var errTruncated = errors.New("item truncado")
+
+func elementsBits(elems []Element) int {
+	total := 0
+	for _, e := range elems {
+		total += e.Bits
+	}
+	return total
+}
+
+func elementsSize(elems []Element) int {
+	return (elementsBits(elems) + 7) / 8
+}
+
+func decodeElements(data []byte, elems []Element, prefix string, out map[string]string) {
+	off := 0
+	for _, e := range elems {
+		if e.Kind != KindSpare && e.Name != "" {
+			appendField(out, prefix+"_"+e.Name, e.format(data, off))
+		}
+		off += e.Bits
+	}
+}
+
+// appendField junta repetições com "," como o tshark faz com occurrence=a.
+func appendField(out map[string]string, key, value string) {
+	if prev, ok := out[key]; ok {
+		out[key] = prev + "," + value
+		return
+	}
+	out[key] = value
+}
+
+func (e Element) format(data []byte, off int) string {
+	switch e.Kind {
+	case KindICAO6, KindASCII:
+		width := 6
+		if e.Kind == KindASCII {
+			width = 8
+		}
+		var sb strings.Builder
+		for i := 0; i+width <= e.Bits; i += width {
+			c := readBits(data, off+i, width)
+			if e.Kind == KindICAO6 {
+				sb.WriteByte(icao6Char(c))
+			} else {
+				sb.WriteByte(byte(c))
+			}
+		}
+		return strings.TrimRight(sb.String(), " ")
+	case KindOctal:
+		return fmt.Sprintf("%0*o", (e.Bits+2)/3, readBits(data, off, e.Bits))
+	case KindBytes:
+		return hex.EncodeToString(data[off/8 : (off+e.Bits)/8])
+	case KindHex:
+		return fmt.Sprintf("0x%0*x", (e.Bits+3)/4, readBits(data, off, e.Bits))
+	}
+
+	v := readBits(data, off, e.Bits)
+	if e.Kind == KindSigned {
+		s := int64(v)
+		if e.Bits < 64 && v&(1<<(e.Bits-1)) != 0 {
+			s -= 1 << e.Bits
+		}
+		if e.LSB != 0 {
+			return strconv.FormatFloat(float64(s)*e.LSB, 'f', -1, 64)
+		}
+		return strconv.FormatInt(s, 10)
+	}
+	if e.LSB != 0 {
+		return strconv.FormatFloat(float64(v)*e.LSB, 'f', -1, 64)
+	}
+	return strconv.FormatUint(v, 10)
+}
+
+// readBits lê n bits (n <= 64) a partir do bit off, MSB primeiro.
+func readBits(data []byte, off, n int) uint64 {
+	var v uint64
+	for i := 0; i < n; i++ {
+		b := data[(off+i)/8]
+		v = v<<1 | uint64(b>>(7-(off+i)%8)&1)
+	}
+	return v
+}
+
+func icao6Char(c uint64) byte {
+	switch {
+	case c >= 1 && c <= 26:
+		return byte('A' + c - 1)
+	case c >= 48 && c <= 57:
+		return byte(c)
+	default:
+		return ' '
+	}
+}
@@ -0,0 +1,251 @@
+package main
+
+import (
+	"bytes"
+	"encoding/hex"
+	"maps"
+	"slices"
+	"strings"
+	"testing"
+)
+
+// block monta um data block ASTERIX com os registros em hexadecimal.
+func block(t *testing.T, cat byte, records ...string) []byte {
+	t.Helper()
+	var body []byte
+	for _, r := range records {
+		b, err := hex.DecodeString(strings.ReplaceAll(r, " ", ""))
+		if err != nil {
+			t.Fatal(err)
+		}
+		body = append(body, b...)
+	}
+	n := 3 + len(body)
+	return append([]byte{cat, byte(n >> 8), byte(n)}, body...)
+}
+
+// registros de referência, campo a campo em ordem de FRN
+const (
+	// 010, 040 (dois octetos), 161, 130, 080, 170
+	cat021Basic = "e5 11 01 01 80" +
+		" 14 81" +
+		" 33 46" +
+		" 01 2c" +
+		" 10 00 00 f8 00 00" +
+		" 3c 65 89" +
+		" 04 20 f1 cb 38 20"
+	// 010, 250 (repetitivo), 295 (compound), SP (explícito)
+	cat021Nested = "81 01 01 01 01 13 02" +
+		" 00 01" +
+		" 02 11 22 33 44 55 66 77 40 00 00 00 00 00 00 01 50" +
+		" 90 0a 19" +
+		" 03 ab cd"
+	// 010, 140, 020, 070, 090, 220, 240
+	cat048Plot = "ed c0" +
+		" 19 0d" +
+		" 07 08 00" +
+		" a4" +
+		" 0f 40" +
+		" 3f ec" +
+		" 4c a1 23" +
+		" 49 94 b1 cb 3d 20"
+)
+
+func TestDecodeRecords(t *testing.T) {
+	tests := []struct {
+		name string
+		cat  byte
+		rec  string
+		want map[string]string
+	}{
+		{"CAT021 fixo, extendido, com sinal e ICAO", 21, cat021Basic, map[string]string{
+			"asterix.021_010_SAC":   "20",
+			"asterix.021_010_SIC":   "129",
+			"asterix.021_040_ATP":   "1",
+			"asterix.021_040_ARC":   "2",
+			"asterix.021_040_RC":    "0",
+			"asterix.021_040_RAB":   "1",
+			"asterix.021_040_DCR":   "0",
+			"asterix.021_040_GBS":   "1",
+			"asterix.021_040_SIM":   "0",
+			"asterix.021_040_TST":   "0",
+			"asterix.021_040_SAA":   "0",
+			"asterix.021_040_CL":    "3",
+			"asterix.021_161_TRNUM": "300",
+			"asterix.021_130_LAT":   "22.5",
+			"asterix.021_130_LON":   "-11.25",
+			"asterix.021_080_VALUE": "0x3c6589",
+			"asterix.021_170_VALUE": "ABC123",
+		}},
+		{"CAT021 repetitivo, compound e explícito", 21, cat021Nested, map[string]string{
+			"asterix.021_010_SAC":       "0",
+			"asterix.021_010_SIC":       "1",
+			"asterix.021_250_MBDATA":    "4822678189205111,1",
+			"asterix.021_250_BDS1":      "4,5",
+			"asterix.021_250_BDS2":      "0,0",
+			"asterix.021_295_AOS_VALUE": "1",
+			"asterix.021_295_QI_VALUE":  "2.5",
+			"asterix.021_SP_VALUE":      "abcd",
+		}},
+		{"CAT048 octal, FL negativo e endereço", 48, cat048Plot, map[string]string{
+			"asterix.048_010_SAC":    "25",
+			"asterix.048_010_SIC":    "13",
+			"asterix.048_140_VALUE":  "3600",
+			"asterix.048_020_TYP":    "5",
+			"asterix.048_020_SIM":    "0",
+			"asterix.048_020_RDP":    "0",
+			"asterix.048_020_SPI":    "1",
+			"asterix.048_020_RAB":    "0",
+			"asterix.048_070_V":      "0",
+			"asterix.048_070_G":      "0",
+			"asterix.048_070_L":      "0",
+			"asterix.048_070_MODE3A": "7500",
+			"asterix.048_090_V":      "0",
+			"asterix.048_090_G":      "0",
+			"asterix.048_090_FL":     "-5",
+			"asterix.048_220_VALUE":  "0x4ca123",
+			"asterix.048_240_VALUE":  "RYR1234",
+		}},
+	}
+	d := NewAsterixDecoder(cat021(), cat048())
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			records, err := d.Decode(block(t, tt.cat, tt.rec))
+			if err != nil {
+				t.Fatal(err)
+			}
+			if len(records) != 1 {
+				t.Fatalf("%d registros, quer 1", len(records))
+			}
+			if got := records[0].Fields; !maps.Equal(got, tt.want) {
+				for k, v := range tt.want {
+					if got[k] != v {
+						t.Errorf("%s = %q, quer %q", k, got[k], v)
+					}
+				}
+				for k, v := range got {
+					if _, ok := tt.want[k]; !ok {
+						t.Errorf("campo inesperado %s = %q", k, v)
+					}
+				}
+			}
+		})
+	}
+}
+
+func TestDecodeBlocks(t *testing.T) {
+	d := NewAsterixDecoder(cat021(), cat048())
+	var data []byte
+	data = append(data, block(t, 21, cat021Basic, cat021Nested)...)
+	data = append(data, block(t, 62, "80 00 01")...) // sem UAP: ignorado
+	data = append(data, block(t, 48, cat048Plot)...)
+
+	records, err := d.Decode(data)
+	if err != nil {
+		t.Fatal(err)
+	}
+	var cats []int
+	for _, r := range records {
+		cats = append(cats, r.Category)
+	}
+	if len(cats) != 3 || cats[0] != 21 || cats[1] != 21 || cats[2] != 48 {
+		t.Fatalf("categorias = %v, quer [21 21 48]", cats)
+	}
+	if sac, sic, ok := records[2].SacSic(); !ok || sac != 25 || sic != 13 {
+		t.Errorf("SacSic = %d/%d %v", sac, sic, ok)
+	}
+}
+
+func TestDecodeErrors(t *testing.T) {
+	tests := []struct {
+		name string
+		data []byte
+		keep int // registros decodificados antes do erro
+	}{
+		{"bloco truncado", []byte{21, 0}, 0},
+		{"tamanho de bloco inválido", []byte{21, 0, 2}, 0},
+		{"FSPEC truncado", block(t, 21, "01"), 0},
+		{"item truncado", block(t, 48, "80 19"), 0},
+		{"FRN sem item", block(t, 21, "01 01 01 01 01 01 80"), 0},
+		{"subcampo não definido", block(t, 21, "81 01 01 01 01 02 00 01 01 01 01 20"), 0},
+		{"explícito truncado", block(t, 21, "81 01 01 01 01 01 02 00 01 05 ab"), 0},
+		{"erro depois de um registro", block(t, 48, cat048Plot, "80 19"), 1},
+	}
+	d := NewAsterixDecoder(cat021(), cat048())
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			records, err := d.Decode(tt.data)
+			if err == nil {
+				t.Fatal("sem erro")
+			}
+			if len(records) != tt.keep {
+				t.Errorf("%d registros antes do erro, quer %d", len(records), tt.keep)
+			}
+		})
+	}
+}
+
+func TestReadFSPEC(t *testing.T) {
+	tests := []struct {
+		data    string
+		present []int // FRNs presentes
+		n       int
+	}{
+		{"80", []int{1}, 1},
+		{"fe", []int{1, 2, 3, 4, 5, 6, 7}, 1},
+		{"01 01 40", []int{16}, 3},
+		{"e5 11 01 01 80", []int{1, 2, 3, 6, 11, 29}, 5},
+	}
+	for _, tt := range tests {
+		data, _ := hex.DecodeString(strings.ReplaceAll(tt.data, " ", ""))
+		fspec, n, err := readFSPEC(data)
+		if err != nil {
+			t.Fatalf("%s: %v", tt.data, err)
+		}
+		var got []int
+		for i, p := range fspec {
+			if p {
+				got = append(got, i+1)
+			}
+		}
+		if n != tt.n || !slices.Equal(got, tt.present) {
+			t.Errorf("%s: FRNs %v em %d octetos, quer %v em %d", tt.data, got, n, tt.present, tt.n)
+		}
+	}
+	if _, _, err := readFSPEC([]byte{0x81}); err == nil {
+		t.Error("FSPEC com FX no último octeto deveria falhar")
+	}
+}
+
+func TestElementFormat(t *testing.T) {
+	tests := []struct {
+		el   Element
+		data []byte
+		want string
+	}{
+		{rawEl("V", 8), []byte{0xff}, "255"},
+		{sEl("V", 8, 0, ""), []byte{0xff}, "-1"},
+		{sEl("V", 16, 0.25, ""), []byte{0x80, 0x00}, "-8192"},
+		{uEl("V", 8, 0.5, ""), []byte{0x03}, "1.5"},
+		{hexEl("V", 24), []byte{0x00, 0x00, 0x0a}, "0x00000a"},
+		{octEl("V", 12), []byte{0x00, 0x08}, "0010"},
+		{Element{Name: "V", Bits: 16, Kind: KindASCII}, []byte("A "), "A"},
+		{Element{Name: "V", Bits: 16, Kind: KindBytes}, []byte{0xde, 0xad}, "dead"},
+		{strEl("V", 48), []byte{0x50, 0x14, 0x20, 0xc7, 0x28, 0x20}, "TAP 12"},
+	}
+	for _, tt := range tests {
+		if got := tt.el.format(tt.data, 0); got != tt.want {
+			t.Errorf("%s %d bits %x = %q, quer %q", tt.el.Kind, tt.el.Bits, tt.data, got, tt.want)
+		}
+	}
+}
+
+func TestICAO6Char(t *testing.T) {
+	var got bytes.Buffer
+	for _, c := range []uint64{1, 26, 32, 48, 57, 0, 27, 63} {
+		got.WriteByte(icao6Char(c))
+	}
+	if want := "AZ 09   "; got.String() != want {
+		t.Errorf("icao6Char = %q, quer %q", got.String(), want)
+	}
+}
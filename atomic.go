@@ -0,0 +1,102 @@
+package main
+
+import (
+	"os"
+	"sync"
+)
+
+//
+// ---------------- ARQUIVOS PARCIAIS ----------------
+//
+
+// As saídas são gravadas em <nome>.partial e renomeadas só quando o sink
+// fecha sem erro; uma captura interrompida não deixa um arquivo truncado
+// com o nome final.
+const partialSuffix = ".partial"
+
+// partials guarda a limpeza de cada arquivo parcial aberto, para os sinais.
+var partials = struct {
+	sync.Mutex
+	m map[string]func()
+}{m: map[string]func(){}}
+
+func trackPartial(path string, cleanup func()) {
+	partials.Lock()
+	partials.m[path] = cleanup
+	partials.Unlock()
+}
+
+func untrackPartial(path string) {
+	partials.Lock()
+	delete(partials.m, path)
+	partials.Unlock()
+}
+
+// removePartials apaga os arquivos parciais ainda abertos. É chamada ao
+// receber SIGINT/SIGTERM.
+func removePartials() {
+	partials.Lock()
+	defer partials.Unlock()
+	for path, cleanup := range partials.m {
+		cleanup()
+		delete(partials.m, path)
+	}
+}
+
+// outputFile é um arquivo de saída gravado com o nome parcial.
+type outputFile struct {
+	*os.File
+	path   string // nome final
+	once   sync.Once
+	closed error
+}
+
+func createOutput(path string) (*outputFile, error) {
+	f, err := os.Create(path + partialSuffix)
+	if err != nil {
+		return nil, err
+	}
+	out := &outputFile{File: f, path: path}
+	trackPartial(f.Name(), func() {
+		out.Close()
+		os.Remove(f.Name())
+	})
+	return out, nil
+}
+
+// Close pode ser chamado mais de uma vez; o writer do parquet fecha o
+// arquivo por conta própria.
+func (f *outputFile) Close() error {
+	f.once.Do(func() { f.closed = f.File.Close() })
+	return f.closed
+}
+
+// commit fecha o arquivo e o renomeia para o nome final.
+func (f *outputFile) commit() error {
+	if err := f.Close(); err != nil {
+		f.abort()
+		return err
+	}
+	untrackPartial(f.Name())
+	if err := os.Rename(f.Name(), f.path); err != nil {
+		os.Remove(f.Name())
+		return err
+	}
+	return nil
+}
+
+// abort fecha e apaga o arquivo parcial.
+func (f *outputFile) abort() {
+	f.Close()
+	os.Remove(f.Name())
+	untrackPartial(f.Name())
+}
+
+// finish confirma o arquivo se err for nil e o descarta caso contrário.
+func (f *outputFile) finish(err error) error {
+	if err != nil {
+		f.abort()
+		return err
+	}
+	return f.commit()
+}
@@ -0,0 +1,307 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"regexp"
+	"strconv"
+	"strings"
+	"text/tabwriter"
+)
+
+//
+// ---------------- CATÁLOGO ----------------
+//
+
+// catalogField é um campo que o decoder nativo produz para uma categoria.
+type catalogField struct {
+	Field string // asterix.021_090_NACP
+	Item  string // 090
+	Path  string // item e subitens: 090, 295_QI
+	Desc  string // nome do item ou subitem
+	Label string
+	Type  string
+	Unit  string
+}
+
+// categoryFields lista os campos de uma categoria na ordem da UAP, com
+// label e type sugeridos.
+func categoryFields(c *Category) []catalogField {
+	var fields []catalogField
+	for _, id := range c.UAP {
+		it, ok := c.Items[id]
+		if id == "" || !ok {
+			continue
+		}
+		fields = it.catalog(fields, c.Number, id, id, it.Name, false)
+	}
+
+	// labels repetidos (LAT em 130 e 131) recebem o item como sufixo
+	count := map[string]int{}
+	for _, f := range fields {
+		count[f.Label]++
+	}
+	for i, f := range fields {
+		if count[f.Label] > 1 {
+			fields[i].Label = f.Label + "_" + f.Path
+		}
+	}
+	return fields
+}
+
+// catalog segue a mesma estrutura de Item.decode. repeated indica itens
+// que podem aparecer mais de uma vez no registro (valores juntados com ",").
+func (it *Item) catalog(out []catalogField, cat int, path, item, desc string, repeated bool) []catalogField {
+	prefix := fmt.Sprintf("asterix.%03d_%s", cat, path)
+	add := func(elems []Element, repeated bool) {
+		for _, e := range elems {
+			if e.Kind == KindSpare || e.Name == "" {
+				continue
+			}
+			label := e.Name
+			if label == "VALUE" {
+				label = labelFromName(desc)
+			}
+			out = append(out, catalogField{
+				Field: prefix + "_" + e.Name,
+				Item:  item,
+				Path:  path,
+				Desc:  desc,
+				Label: label,
+				Type:  elementType(e, repeated),
+				Unit:  e.Unit,
+			})
+		}
+	}
+	switch it.Format {
+	case FormatExtended:
+		for _, g := range it.Groups {
+			add(g, repeated)
+		}
+	case FormatRepetitive:
+		add(it.Elements, true)
+	case FormatCompound:
+		for _, sub := range it.Subitems {
+			if sub == nil {
+				continue
+			}
+			name := sub.Name
+			if name == "" {
+				name = desc + " " + sub.ID
+			}
+			out = sub.catalog(out, cat, path+"_"+sub.ID, item, name, repeated)
+		}
+	case FormatExplicit:
+		if len(it.Elements) == 0 {
+			out = append(out, catalogField{
+				Field: prefix + "_VALUE",
+				Item:  item,
+				Path:  path,
+				Desc:  desc,
+				Label: labelFromName(desc),
+				Type:  "string", // conteúdo em hexadecimal
+			})
+		}
+		add(it.Elements, true)
+	default:
+		add(it.Elements, repeated)
+	}
+	return out
+}
+
+// elementType sugere o type da coluna a partir do layout do elemento.
+func elementType(e Element, repeated bool) string {
+	var t string
+	switch {
+	case e.Kind == KindICAO6 && !repeated:
+		t = "dict" // callsign, identificação: poucos valores distintos
+	case e.Kind == KindICAO6, e.Kind == KindASCII, e.Kind == KindOctal, e.Kind == KindBytes, e.Kind == KindHex:
+		t = "string"
+	case e.LSB != 0:
+		t = "float64"
+	case e.Kind == KindSigned:
+		t = "int" + strconv.Itoa(intBits(e.Bits))
+	case e.Bits == 1:
+		t = "bool"
+	default:
+		t = "uint" + strconv.Itoa(intBits(e.Bits))
+	}
+	if repeated {
+		return "list<" + t + ">"
+	}
+	return t
+}
+
+func intBits(bits int) int {
+	switch {
+	case bits <= 8:
+		return 8
+	case bits <= 16:
+		return 16
+	case bits <= 32:
+		return 32
+	}
+	return 64
+}
+
+var labelSeparators = regexp.MustCompile(`[^A-Za-z0-9]+`)
+
+// labelFromName transforma o nome do item em label: "Track Number" -> TRACK_NUMBER.
+func labelFromName(name string) string {
+	return strings.Trim(strings.ToUpper(labelSeparators.ReplaceAllString(name, "_")), "_")
+}
+
+// catalogCategories interpreta -category (021, 21 ou CAT021, separados por
+// vírgula) e escolhe a edição mais nova de cada uma.
+func catalogCategories(specs *SpecSet, arg string) ([]*Category, error) {
+	var cats []*Category
+	for _, s := range splitList(arg) {
+		n, err := strconv.Atoi(strings.TrimPrefix(strings.ToUpper(s), "CAT"))
+		if err != nil {
+			return nil, fmt.Errorf("categoria inválida %q", s)
+		}
+		eds := specs.Editions(n)
+		if len(eds) == 0 {
+			return nil, fmt.Errorf("CAT%03d sem especificação (use decoder.specs ou -specs)", n)
+		}
+		cats = append(cats, specs.cats[n][eds[len(eds)-1]])
+	}
+	if len(cats) == 0 {
+		return nil, fmt.Errorf("use -category 021[,048...]")
+	}
+	return cats, nil
+}
+
+// catalogSpecs carrega as UAPs embutidas e as de -specs ou, se houver
+// configuração, as de decoder.specs. Retorna também o diretório usado,
+// absoluto, ou "" para só as embutidas.
+func catalogSpecs(cfgPath, specsDir string) (*SpecSet, string, error) {
+	var cfg Config
+	if specsDir != "" {
+		cfg.Decoder.Specs = specsDir
+	} else if _, err := os.Stat(cfgPath); err == nil {
+		if cfg, err = loadConfig(cfgPath); err != nil {
+			return nil, "", fmt.Errorf("%s: %w", cfgPath, err)
+		}
+	}
+	specs, err := loadSpecs(cfg)
+	if err != nil || cfg.Decoder.Specs == "" {
+		return specs, "", err
+	}
+	dir, err := filepath.Abs(cfg.Decoder.Specs)
+	return specs, dir, err
+}
+
+// runFields implementa "pshark fields": lista os campos de cada categoria.
+func runFields(args []string) int {
+	fs := flag.NewFlagSet("fields", flag.ExitOnError)
+	category := fs.String("category", "", "Categorias separadas por vírgula, ex. 021,048")
+	cfgPath := fs.String("cfg", "config.toml", "Config file (para decoder.specs, se existir)")
+	specsDir := fs.String("specs", "", "Diretório com especificações XML/JSON")
+	fs.Parse(args)
+
+	specs, _, err := catalogSpecs(*cfgPath, *specsDir)
+	if err != nil {
+		fmt.Println("❌", err)
+		return exitConfig
+	}
+	cats, err := catalogCategories(specs, *category)
+	if err != nil {
+		fmt.Println("❌", err)
+		return exitConfig
+	}
+
+	tw := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	for _, c := range cats {
+		fmt.Fprintf(tw, "CAT%03d edição %s\n", c.Number, c.Edition)
+		fmt.Fprintln(tw, "field\ttype\tunit\tlabel\titem")
+		for _, f := range categoryFields(c) {
+			fmt.Fprintf(tw, "%s\t%s\t%s\t%s\t%s %s\n", f.Field, f.Type, f.Unit, f.Label, f.Item, f.Desc)
+		}
+		fmt.Fprintln(tw)
+	}
+	tw.Flush()
+	return exitOK
+}
+
+// runInit implementa "pshark init": gera uma configuração com um datagroup
+// por categoria, com todos os campos, na saída padrão.
+func runInit(args []string) int {
+	fs := flag.NewFlagSet("init", flag.ExitOnError)
+	category := fs.String("category", "", "Categorias separadas por vírgula, ex. 021,048,062")
+	cfgPath := fs.String("cfg", "config.toml", "Config file (para decoder.specs, se existir)")
+	specsDir := fs.String("specs", "", "Diretório com especificações XML/JSON")
+	fs.Parse(args)
+
+	// a configuração gerada aponta para as mesmas especificações, mesmo
+	// quando vieram do decoder.specs de -cfg
+	specs, dir, err := catalogSpecs(*cfgPath, *specsDir)
+	if err != nil {
+		fmt.Println("❌", err)
+		return exitConfig
+	}
+	cats, err := catalogCategories(specs, *category)
+	if err != nil {
+		fmt.Println("❌", err)
+		return exitConfig
+	}
+	writeInitConfig(os.Stdout, cats, dir)
+	return exitOK
+}
+
+func writeInitConfig(w io.Writer, cats []*Category, specsDir string) {
+	fmt.Fprintln(w, `[decoder]`)
+	fmt.Fprintln(w, `backend = "native"`)
+	if specsDir != "" {
+		fmt.Fprintf(w, "specs = %q\n", specsDir)
+	}
+	fmt.Fprintln(w)
+	fmt.Fprintln(w, `[output]`)
+	fmt.Fprintln(w, `formats = ["parquet"]`)
+
+	frame := []catalogField{
+		{Label: "TIMESTAMP", Field: "frame.time_epoch", Type: "timestamp"},
+		{Label: "SRC_IP", Field: "ip.src", Type: "string"},
+		{Label: "SRC_PORT", Field: "udp.srcport", Type: "uint16"},
+		{Label: "DST_IP", Field: "ip.dst", Type: "string"},
+		{Label: "DST_PORT", Field: "udp.dstport", Type: "uint16"},
+	}
+	for _, f := range frame {
+		fmt.Fprintln(w)
+		fmt.Fprintln(w, "[[frame]]")
+		writeInitField(w, f)
+	}
+
+	fmt.Fprintln(w)
+	fmt.Fprintln(w, "[datagroup]")
+	for _, c := range cats {
+		name := fmt.Sprintf("cat%03d", c.Number)
+		fmt.Fprintln(w)
+		fmt.Fprintf(w, "[datagroup.%s]\n", name)
+		fmt.Fprintf(w, "editions = { %q = %q }\n", fmt.Sprintf("%03d", c.Number), c.Edition)
+		fmt.Fprintf(w, "categories = [%d]\n", c.Number)
+
+		item := ""
+		for _, f := range categoryFields(c) {
+			fmt.Fprintln(w)
+			if f.Item != item {
+				fmt.Fprintf(w, "# I%03d/%s %s\n", c.Number, f.Item, f.Desc)
+				item = f.Item
+			}
+			fmt.Fprintf(w, "[[datagroup.%s.fields]]\n", name)
+			writeInitField(w, f)
+		}
+	}
+}
+
+func writeInitField(w io.Writer, f catalogField) {
+	fmt.Fprintf(w, "label = %q\n", f.Label)
+	fmt.Fprintf(w, "field = %q\n", f.Field)
+	fmt.Fprintf(w, "type  = %q\n", f.Type)
+	if f.Unit != "" {
+		fmt.Fprintf(w, "unit  = %q\n", f.Unit)
+	}
+}
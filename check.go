@@ -0,0 +1,305 @@
+package main
+
+import (
+	"bufio"
+	"flag"
+	"fmt"
+	"os/exec"
+	"sort"
+	"strings"
+)
+
+//
+// ---------------- CHECK ----------------
+//
+
+// campos de quadro entregues pelo backend nativo
+var nativeFrameFields = []string{"frame.time_epoch", "ip.src", "ip.dst", "udp.srcport", "udp.dstport"}
+
+// checkReport acumula os problemas encontrados por pshark check.
+type checkReport struct {
+	errors   int
+	warnings int
+}
+
+func (r *checkReport) errorf(format string, args ...any) {
+	r.errors++
+	fmt.Printf("❌ "+format+"\n", args...)
+}
+
+func (r *checkReport) warnf(format string, args ...any) {
+	r.warnings++
+	fmt.Printf("⚠ "+format+"\n", args...)
+}
+
+// runCheck implementa "pshark check": valida a configuração sem ler
+// capturas. Retorna exitConfig se houver erros.
+func runCheck(args []string) int {
+	fs := flag.NewFlagSet("check", flag.ExitOnError)
+	cfgPath := fs.String("cfg", "config.toml", "Config file")
+	datagroup := fs.String("g", "all", "Datagroups separados por vírgula, ou all")
+	fs.Parse(args)
+
+	cfg, err := readConfig(*cfgPath)
+	if err != nil {
+		fmt.Println("❌ config:", err)
+		return exitConfig
+	}
+	specs, err := loadSpecs(cfg)
+	if err != nil {
+		fmt.Println("❌ specs:", err)
+		return exitConfig
+	}
+
+	// todos os erros de validação, não só o primeiro como em loadConfig
+	r := &checkReport{}
+	for _, err := range cfg.validate() {
+		r.errorf("%v", err)
+	}
+	if len(cfg.Datagroup) == 0 {
+		r.errorf("nenhum datagroup configurado")
+	}
+	var names []string
+	for _, name := range datagroupNames(cfg, *datagroup) {
+		if _, ok := cfg.Datagroup[name]; !ok {
+			r.errorf("datagroup %s não existe na configuração", name)
+			continue
+		}
+		names = append(names, name)
+	}
+
+	// os nomes de campo válidos vêm do tshark ou das especificações
+	var tsharkFields map[string]bool
+	if cfg.Decoder.Backend == "tshark" {
+		cfg.Tshark.warnIgnored(r.warnf)
+		if tsharkFields, err = loadTsharkFields(cfg.Tshark.Path); err != nil {
+			r.errorf("tshark -G fields: %v", err)
+		}
+	}
+
+	// [[frame]] entra em todos os datagroups, mas é verificado uma vez só
+	frameLabels := checkFields(r, "frame", cfg.Frame, nil)
+	frameReported := map[string]bool{}
+
+	for _, name := range names {
+		dg := cfg.Datagroup[name]
+		prefix := "datagroup." + name
+		if len(dg.Fields) == 0 {
+			r.warnf("%s: nenhum campo", prefix)
+		}
+		checkFields(r, prefix, dg.Fields, frameLabels)
+
+		if _, err := newGroups(cfg, specs, []string{name}); err != nil {
+			r.errorf("%v", err)
+			continue
+		}
+
+		known := tsharkFields
+		if cfg.Decoder.Backend != "tshark" {
+			dec, err := specs.Resolve(cfg.editionsFor(name))
+			if err != nil {
+				continue // já reportado por newGroups
+			}
+			known = dec.fieldNames()
+			elems := dec.fieldElements()
+			for _, f := range dg.Fields {
+				if e, ok := elems[f.Field]; ok && e.LSB != 0 && (f.Scale != 0 || f.Offset != 0) {
+					r.warnf("%s: %s: scale/offset ignorado, o decoder nativo já entrega %s em unidade de engenharia", prefix, f.Label, f.Field)
+				}
+			}
+		}
+		if known == nil {
+			continue
+		}
+		checkKnown(r, "frame", cfg.Frame, known, frameReported)
+		checkKnown(r, prefix, dg.Fields, known, nil)
+	}
+
+	if r.errors > 0 {
+		fmt.Printf("%d erros, %d avisos\n", r.errors, r.warnings)
+		return exitConfig
+	}
+	fmt.Printf("✔ %s: %d datagroups ok, %d avisos\n", *cfgPath, len(names), r.warnings)
+	return exitOK
+}
+
+// checkFields verifica labels e a origem de cada coluna; types e demais
+// opções passam por DataItem.validate em Config.validate. frame são as
+// colunas de [[frame]], que não podem se repetir no datagroup. Retorna as
+// colunas vistas, incluindo as <LABEL>_RAW de on_error = "keep_raw".
+func checkFields(r *checkReport, prefix string, fields []DataItem, frame map[string]bool) map[string]bool {
+	seen := map[string]bool{}
+	for i, f := range fields {
+		label := f.Label
+		switch {
+		case label == "":
+			label = fmt.Sprintf("campo %d", i+1)
+			r.errorf("%s: %s sem label", prefix, label)
+		case seen[label]:
+			r.errorf("%s: label %s repetido", prefix, label)
+		case frame[label]:
+			r.errorf("%s: label %s repetido (já usado em [[frame]])", prefix, label)
+		}
+		seen[label] = true
+
+		if f.Field == "" && f.Expr == "" {
+			r.errorf("%s: %s sem field nem expr", prefix, label)
+		}
+	}
+	// a coluna extra de keep_raw (ver newConverter) também precisa ser única
+	for _, f := range fields {
+		if f.Label == "" || f.OnError != OnErrorKeepRaw || f.Type == "" || f.Type == "string" {
+			continue
+		}
+		raw := f.Label + "_RAW"
+		if seen[raw] || frame[raw] {
+			r.errorf("%s: %s: a coluna %s de on_error = \"keep_raw\" repete um label", prefix, f.Label, raw)
+		}
+		seen[raw] = true
+	}
+	return seen
+}
+
+// checkKnown reporta os fields que o decoder não produz. reported evita
+// repetir o mesmo campo de [[frame]] em cada datagroup.
+func checkKnown(r *checkReport, prefix string, fields []DataItem, known, reported map[string]bool) {
+	for _, f := range fields {
+		if f.Field == "" || known[f.Field] || reported[f.Field] {
+			continue
+		}
+		if reported != nil {
+			reported[f.Field] = true
+		}
+		msg := fmt.Sprintf("%s: %s: field %s não existe", prefix, f.Label, f.Field)
+		if s := suggestFields(f.Field, known); len(s) > 0 {
+			msg += "; você quis dizer " + strings.Join(s, " ou ") + "?"
+		}
+		r.errorf("%s", msg)
+	}
+}
+
+// loadTsharkFields lista os campos que o tshark conhece (tshark -G fields).
+func loadTsharkFields(path string) (map[string]bool, error) {
+	out, err := exec.Command(path, "-G", "fields").Output()
+	if err != nil {
+		return nil, err
+	}
+	fields := map[string]bool{}
+	scanner := bufio.NewScanner(strings.NewReader(string(out)))
+	for scanner.Scan() {
+		// F<tab>nome<tab>abreviação<tab>... e P<tab>protocolo<tab>abreviação
+		cols := strings.Split(scanner.Text(), "\t")
+		if len(cols) >= 3 && (cols[0] == "F" || cols[0] == "P") {
+			fields[cols[2]] = true
+		}
+	}
+	return fields, scanner.Err()
+}
+
+// fieldNames lista os campos que o decoder nativo produz, mais os campos
+// de quadro.
+func (d *AsterixDecoder) fieldNames() map[string]bool {
+	names := map[string]bool{}
+	for _, f := range nativeFrameFields {
+		names[f] = true
+	}
+	for f := range d.fieldElements() {
+		names[f] = true
+	}
+	return names
+}
+
+// fieldElements associa cada campo ASTERIX que o decoder nativo produz ao
+// elemento da UAP que o gera.
+func (d *AsterixDecoder) fieldElements() map[string]Element {
+	elems := map[string]Element{}
+	for _, c := range d.cats {
+		for _, it := range c.Items {
+			it.fieldElements(fmt.Sprintf("asterix.%03d_%s", c.Number, it.ID), elems)
+		}
+	}
+	return elems
+}
+
+func (it *Item) fieldElements(prefix string, out map[string]Element) {
+	add := func(elems []Element) {
+		for _, e := range elems {
+			if e.Kind != KindSpare && e.Name != "" {
+				out[prefix+"_"+e.Name] = e
+			}
+		}
+	}
+	switch it.Format {
+	case FormatExtended:
+		for _, g := range it.Groups {
+			add(g)
+		}
+	case FormatCompound:
+		for _, sub := range it.Subitems {
+			if sub != nil {
+				sub.fieldElements(prefix+"_"+sub.ID, out)
+			}
+		}
+	case FormatExplicit:
+		if len(it.Elements) == 0 {
+			out[prefix+"_VALUE"] = Element{Name: "VALUE", Kind: KindBytes}
+		}
+		add(it.Elements)
+	default:
+		add(it.Elements)
+	}
+}
+
+// suggestFields retorna até três campos conhecidos próximos de name, do
+// mesmo protocolo.
+func suggestFields(name string, known map[string]bool) []string {
+	proto, _, _ := strings.Cut(name, ".")
+	limit := max(2, len(name)/6)
+
+	type candidate struct {
+		name string
+		dist int
+	}
+	var found []candidate
+	for k := range known {
+		if p, _, _ := strings.Cut(k, "."); p != proto {
+			continue
+		}
+		if d := editDistance(strings.ToLower(name), strings.ToLower(k)); d <= limit {
+			found = append(found, candidate{k, d})
+		}
+	}
+	sort.Slice(found, func(i, j int) bool {
+		if found[i].dist != found[j].dist {
+			return found[i].dist < found[j].dist
+		}
+		return found[i].name < found[j].name
+	})
+
+	var out []string
+	for i := 0; i < len(found) && i < 3; i++ {
+		out = append(out, found[i].name)
+	}
+	return out
+}
+
+// editDistance é a distância de Levenshtein.
+func editDistance(a, b string) int {
+	prev := make([]int, len(b)+1)
+	cur := make([]int, len(b)+1)
+	for j := range prev {
+		prev[j] = j
+	}
+	for i := 1; i <= len(a); i++ {
+		cur[0] = i
+		for j := 1; j <= len(b); j++ {
+			cost := 1
+			if a[i-1] == b[j-1] {
+				cost = 0
+			}
+			cur[j] = min(prev[j]+1, cur[j-1]+1, prev[j-1]+cost)
+		}
+		prev, cur = cur, prev
+	}
+	return prev[len(b)]
+}
@@ -0,0 +1,340 @@
+package main
+
+import (
+	"fmt"
+	"slices"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/apache/arrow/go/v14/arrow"
+)
+
+//
+// ---------------- CONVERSÃO ----------------
+//
+
+// políticas de erro de conversão (on_error)
+const (
+	OnErrorNull    = "null"     // grava null (padrão)
+	OnErrorKeepRaw = "keep_raw" // grava null e o texto original em <LABEL>_RAW
+	OnErrorFail    = "fail"     // interrompe o arquivo
+)
+
+// parseValue converte o texto para o tipo Go da coluna, sem truncar nem
+// dar a volta em overflow.
+func (f DataItem) parseValue(v string) (any, error) {
+	return parseTyped(f.Type, v)
+}
+
+func parseTyped(t, v string) (any, error) {
+	if elem, ok := listElem(t); ok {
+		// itens repetitivos chegam separados por vírgula
+		parts := strings.Split(v, ",")
+		list := make([]any, len(parts))
+		for i, p := range parts {
+			x, err := parseTyped(elem, strings.TrimSpace(p))
+			if err != nil {
+				return nil, err
+			}
+			list[i] = x
+		}
+		return list, nil
+	}
+
+	switch t {
+	case "bool":
+		b, ok := parseBool(v)
+		if !ok {
+			return false, fmt.Errorf("booleano inválido %q", v)
+		}
+		return b, nil
+	case "int8":
+		x, err := parseInt(v, 8)
+		return int8(x), err
+	case "int16":
+		x, err := parseInt(v, 16)
+		return int16(x), err
+	case "int32":
+		x, err := parseInt(v, 32)
+		return int32(x), err
+	case "int64":
+		return parseInt(v, 64)
+	case "uint8":
+		x, err := parseUint(v, 8)
+		return uint8(x), err
+	case "uint16":
+		x, err := parseUint(v, 16)
+		return uint16(x), err
+	case "uint32":
+		x, err := parseUint(v, 32)
+		return uint32(x), err
+	case "uint64":
+		return parseUint(v, 64)
+	case "float32":
+		x, err := parseFloat(v, 32)
+		return float32(x), err
+	case "float64":
+		return parseFloat(v, 64)
+	case "timestamp":
+		return parseTimestamp(v)
+	default:
+		return v, nil
+	}
+}
+
+// parseTimestamp aceita segundos desde a época ("1700000000.123456789",
+// sem passar por float64 para não perder precisão) ou RFC 3339.
+func parseTimestamp(v string) (arrow.Timestamp, error) {
+	if t, err := time.Parse(time.RFC3339Nano, v); err == nil {
+		return arrow.Timestamp(t.UnixMicro()), nil
+	}
+	sec, frac, _ := strings.Cut(v, ".")
+	s, err := strconv.ParseInt(sec, 10, 64)
+	if err != nil {
+		return 0, err
+	}
+	frac = (frac + "000000")[:6]
+	us, err := strconv.ParseInt(frac, 10, 64)
+	if err != nil {
+		return 0, err
+	}
+	if strings.HasPrefix(sec, "-") {
+		us = -us
+	}
+	return arrow.Timestamp(s*1_000_000 + us), nil
+}
+
+// parseBool aceita as formas usadas pelo tshark e pelo decoder nativo.
+func parseBool(v string) (bool, bool) {
+	switch strings.ToLower(v) {
+	case "1", "true", "yes", "set":
+		return true, true
+	case "0", "false", "no", "not set":
+		return false, true
+	}
+	return false, false
+}
+
+func parseInt(v string, bits int) (int64, error) {
+	if b, ok := parseBool(v); ok && !isDigits(v) {
+		if b {
+			return 1, nil
+		}
+		return 0, nil
+	}
+	return parseIntDigits(v, bits)
+}
+
+// parseIntDigits lê um inteiro decimal ou hexadecimal (0x3C, -0x80).
+func parseIntDigits(v string, bits int) (int64, error) {
+	// o sinal entra no ParseInt para aceitar o mínimo (-0x80 em int8)
+	if digits, base := hexDigits(strings.TrimPrefix(v, "-")); base == 16 {
+		if strings.HasPrefix(v, "-") {
+			digits = "-" + digits
+		}
+		return strconv.ParseInt(digits, 16, bits)
+	}
+	return strconv.ParseInt(v, 10, bits)
+}
+
+func parseUint(v string, bits int) (uint64, error) {
+	if b, ok := parseBool(v); ok && !isDigits(v) {
+		if b {
+			return 1, nil
+		}
+		return 0, nil
+	}
+	digits, base := hexDigits(v)
+	return strconv.ParseUint(digits, base, bits)
+}
+
+func parseFloat(v string, bits int) (float64, error) {
+	if digits, base := hexDigits(v); base == 16 {
+		x, err := strconv.ParseUint(digits, 16, 64)
+		return float64(x), err
+	}
+	return strconv.ParseFloat(v, bits)
+}
+
+// hexDigits separa o prefixo 0x. Sem prefixo o valor é decimal (zeros à
+// esquerda não indicam octal).
+func hexDigits(v string) (string, int) {
+	if len(v) > 2 && (v[:2] == "0x" || v[:2] == "0X") {
+		return v[2:], 16
+	}
+	return v, 10
+}
+
+func isDigits(v string) bool {
+	for _, r := range v {
+		if r < '0' || r > '9' {
+			return false
+		}
+	}
+	return v != ""
+}
+
+// Converter transforma as linhas de texto em valores tipados, aplicando
+// escala e a política de erro de cada coluna, e conta as falhas.
+type Converter struct {
+	fields  []DataItem
+	exprs   []*Expr
+	columns []DataItem // colunas de saída, incluindo as <LABEL>_RAW
+	rawCol  []int      // índice da coluna _RAW de cada campo, -1 se não houver
+	errors  []int
+	where   *Expr
+	dropped int
+	env     map[string]any // valores da linha atual para as expressões
+	failed  []convFailure  // falhas da linha atual, contadas se ela for mantida
+}
+
+// convFailure é uma falha de conversão da linha atual.
+type convFailure struct {
+	col int
+	err error
+}
+
+func newConverter(fields []DataItem, exprs []*Expr, where *Expr) *Converter {
+	c := &Converter{
+		fields:  fields,
+		exprs:   exprs,
+		where:   where,
+		columns: append([]DataItem{}, fields...),
+		rawCol:  make([]int, len(fields)),
+		errors:  make([]int, len(fields)),
+		env:     map[string]any{},
+	}
+	for i, f := range fields {
+		c.rawCol[i] = -1
+		if f.OnError == OnErrorKeepRaw && f.Type != "string" && f.Type != "" {
+			c.rawCol[i] = len(c.columns)
+			c.columns = append(c.columns, DataItem{Label: f.Label + "_RAW", Field: f.Field, Type: "string"})
+		}
+	}
+	// compileExprs devolve um slice do tamanho de fields; sem nenhuma
+	// expressão, evalExprs não precisa montar o env a cada linha
+	if !slices.ContainsFunc(exprs, func(e *Expr) bool { return e != nil }) {
+		c.exprs = nil
+	}
+	return c
+}
+
+// Columns retorna o schema de saída.
+func (c *Converter) Columns() []DataItem {
+	return c.columns
+}
+
+// Convert converte a linha e avalia o where. keep = false quando o where
+// descarta a linha; as falhas de conversão de uma linha descartada não
+// contam no resumo nem interrompem o arquivo com on_error = "fail".
+func (c *Converter) Convert(row []string) (out []any, keep bool, err error) {
+	out = make([]any, len(c.columns))
+	c.failed = c.failed[:0]
+	for i, f := range c.fields {
+		if row[i] == "" || f.Expr != "" {
+			continue
+		}
+		raw := row[i]
+		if f.TOD == "" {
+			raw = f.scaleValue(raw) // com tod, aplicado antes em buildRow
+		}
+		v, err := f.parseValue(raw)
+		if err == nil {
+			out[i] = v
+			continue
+		}
+		c.fail(i, fmt.Errorf("valor %q inválido para %s", row[i], f.Type), row[i], out)
+	}
+	c.evalExprs(out)
+
+	if !c.keep() {
+		c.dropped++
+		return nil, false, nil
+	}
+	for _, fl := range c.failed {
+		c.errors[fl.col]++
+		if f := c.fields[fl.col]; f.OnError == OnErrorFail && err == nil {
+			err = fmt.Errorf("coluna %s: %w", f.Label, fl.err)
+		}
+	}
+	if err != nil {
+		return nil, false, err
+	}
+	return out, true, nil
+}
+
+// evalExprs calcula as colunas derivadas na ordem da configuração, de modo
+// que uma expressão pode usar o resultado das anteriores.
+func (c *Converter) evalExprs(out []any) {
+	if c.exprs == nil && c.where == nil {
+		return
+	}
+	for i, f := range c.fields {
+		c.env[f.Label] = exprValue(out[i])
+	}
+	for i, e := range c.exprs {
+		if e == nil {
+			continue
+		}
+		f := c.fields[i]
+		v, err := e.Eval(c.env)
+		if err != nil {
+			c.failed = append(c.failed, convFailure{i, fmt.Errorf("%s: %w", e.src, err)})
+			v = nil
+		} else if x, err := castValue(f.Type, v); err != nil {
+			raw := formatExprValue(v)
+			c.fail(i, fmt.Errorf("valor %q inválido para %s", raw, f.Type), raw, out)
+			v = nil
+		} else {
+			v = x
+		}
+		out[i] = v
+		c.env[f.Label] = exprValue(v)
+	}
+}
+
+// keep avalia o where sobre a linha convertida. Null ou erro na avaliação
+// descartam a linha.
+func (c *Converter) keep() bool {
+	if c.where == nil {
+		return true
+	}
+	v, err := c.where.Eval(c.env)
+	return err == nil && truthy(v)
+}
+
+// Dropped retorna quantas linhas o where descartou.
+func (c *Converter) Dropped() int {
+	return c.dropped
+}
+
+// fail registra um valor inválido do campo i. Com keep_raw o texto vai para
+// <LABEL>_RAW; a política fail é aplicada em Convert, depois do where.
+func (c *Converter) fail(i int, err error, raw string, out []any) {
+	c.failed = append(c.failed, convFailure{i, err})
+	if c.fields[i].OnError == OnErrorKeepRaw && c.rawCol[i] >= 0 {
+		out[c.rawCol[i]] = raw
+	}
+}
+
+// Summary lista as colunas com falhas de conversão, ou "" se não houve.
+func (c *Converter) Summary() string {
+	var parts []string
+	for i, n := range c.errors {
+		if n > 0 {
+			parts = append(parts, fmt.Sprintf("%s=%d", c.fields[i].Label, n))
+		}
+	}
+	sort.Strings(parts)
+	return strings.Join(parts, ", ")
+}
+
+func validOnError(s string) bool {
+	switch s {
+	case "", OnErrorNull, OnErrorKeepRaw, OnErrorFail:
+		return true
+	}
+	return false
+}
@@ -0,0 +1,237 @@
+package main
+
+import (
+	"bufio"
+	"fmt"
+	"strconv"
+	"strings"
+
+	"github.com/apache/arrow/go/v14/arrow"
+)
+
+//
+// ---------------- CSV ----------------
+//
+
+// CSVOptions vem de [output.csv] e pode ser sobreposta por
+// [datagroup.x.output.csv].
+type CSVOptions struct {
+	Delimiter string `toml:"delimiter"`      // padrão ";"
+	Decimal   string `toml:"decimal"`        // "." (padrão) ou ","
+	Quote     string `toml:"quote"`          // minimal (padrão), all ou none
+	Header    *bool  `toml:"header"`         // padrão true
+	Precision *int   `toml:"precision"`      // casas decimais dos floats; padrão o mínimo exato
+	ListSep   string `toml:"list_separator"` // entre os elementos de list<T>; padrão "," ou "|" com decimal = ","
+}
+
+func (o CSVOptions) merge(over CSVOptions) CSVOptions {
+	if over.Delimiter != "" {
+		o.Delimiter = over.Delimiter
+	}
+	if over.Decimal != "" {
+		o.Decimal = over.Decimal
+	}
+	if over.Quote != "" {
+		o.Quote = over.Quote
+	}
+	if over.Header != nil {
+		o.Header = over.Header
+	}
+	if over.Precision != nil {
+		o.Precision = over.Precision
+	}
+	if over.ListSep != "" {
+		o.ListSep = over.ListSep
+	}
+	return o
+}
+
+func (o CSVOptions) validate() error {
+	if len([]rune(o.delimiter())) != 1 {
+		return fmt.Errorf("delimiter deve ter um caractere: %q", o.Delimiter)
+	}
+	switch o.Decimal {
+	case "", ".", ",":
+	default:
+		return fmt.Errorf("decimal inválido %q", o.Decimal)
+	}
+	if o.Decimal == o.delimiter() {
+		return fmt.Errorf("decimal e delimiter não podem ser iguais")
+	}
+	if o.listSep() == o.Decimal || o.listSep() == "." {
+		return fmt.Errorf("list_separator %q se confunde com o separador decimal", o.listSep())
+	}
+	if o.listSep() == o.delimiter() {
+		return fmt.Errorf("list_separator e delimiter não podem ser iguais")
+	}
+	switch o.Quote {
+	case "", "minimal", "all", "none":
+	default:
+		return fmt.Errorf("quote inválido %q", o.Quote)
+	}
+	return nil
+}
+
+func (o CSVOptions) delimiter() string {
+	if o.Delimiter == "" {
+		return ";"
+	}
+	return o.Delimiter
+}
+
+// listSep evita que "1,5,2,5" seja lido como dois números com decimal = ",".
+func (o CSVOptions) listSep() string {
+	switch {
+	case o.ListSep != "":
+		return o.ListSep
+	case o.Decimal == ",":
+		return "|"
+	}
+	return ","
+}
+
+// CSVWriter grava as linhas à medida que chegam, sem passar pelo parquet.
+type CSVWriter struct {
+	file  *outputFile
+	w     *bufio.Writer
+	opts  CSVOptions
+	delim string
+	sep   string
+	line  []string
+}
+
+func newCSVWriter(path string, fields []DataItem, opts CSVOptions) (*CSVWriter, error) {
+	f, err := createOutput(path)
+	if err != nil {
+		return nil, err
+	}
+	c := &CSVWriter{
+		file:  f,
+		w:     bufio.NewWriterSize(f, 1<<20),
+		opts:  opts,
+		delim: opts.delimiter(),
+		sep:   opts.listSep(),
+		line:  make([]string, len(fields)),
+	}
+	if opts.Header == nil || *opts.Header {
+		for i, fd := range fields {
+			c.line[i] = fd.Label
+		}
+		if err := c.writeLine(); err != nil {
+			f.abort()
+			return nil, err
+		}
+	}
+	return c, nil
+}
+
+func (c *CSVWriter) WriteRow(values []any) error {
+	for i, v := range values {
+		c.line[i] = c.format(v)
+	}
+	return c.writeLine()
+}
+
+func (c *CSVWriter) Close() error {
+	return c.file.finish(c.w.Flush())
+}
+
+func (c *CSVWriter) Abort() {
+	c.file.abort()
+}
+
+func (c *CSVWriter) writeLine() error {
+	for i, s := range c.line {
+		if i > 0 {
+			c.w.WriteString(c.delim)
+		}
+		if c.needsQuote(s) {
+			s = `"` + strings.ReplaceAll(s, `"`, `""`) + `"`
+		}
+		c.w.WriteString(s)
+	}
+	_, err := c.w.WriteString("\n")
+	return err
+}
+
+func (c *CSVWriter) needsQuote(s string) bool {
+	switch c.opts.Quote {
+	case "all":
+		return true
+	case "none":
+		return false
+	}
+	return s != "" && (strings.Contains(s, c.delim) || strings.ContainsAny(s, "\"\r\n") || s[0] == ' ')
+}
+
+// format converte os valores tipados de Converter para texto.
+func (c *CSVWriter) format(v any) string {
+	switch x := v.(type) {
+	case nil:
+		return ""
+	case float32:
+		return c.float(float64(x), 32)
+	case float64:
+		return c.float(x, 64)
+	case []any:
+		parts := make([]string, len(x))
+		for i, e := range x {
+			parts[i] = c.format(e)
+		}
+		return strings.Join(parts, c.sep)
+	}
+	return formatValue(v)
+}
+
+func (c *CSVWriter) float(x float64, bits int) string {
+	prec := -1
+	if c.opts.Precision != nil {
+		prec = *c.opts.Precision
+	}
+	s := strconv.FormatFloat(x, 'f', prec, bits)
+	if c.opts.Decimal == "," {
+		s = strings.Replace(s, ".", ",", 1)
+	}
+	return s
+}
+
+// formatValue é a forma textual padrão de um valor convertido.
+func formatValue(v any) string {
+	switch x := v.(type) {
+	case nil:
+		return ""
+	case string:
+		return x
+	case bool:
+		return strconv.FormatBool(x)
+	case int8:
+		return strconv.FormatInt(int64(x), 10)
+	case int16:
+		return strconv.FormatInt(int64(x), 10)
+	case int32:
+		return strconv.FormatInt(int64(x), 10)
+	case int64:
+		return strconv.FormatInt(x, 10)
+	case uint8:
+		return strconv.FormatUint(uint64(x), 10)
+	case uint16:
+		return strconv.FormatUint(uint64(x), 10)
+	case uint32:
+		return strconv.FormatUint(uint64(x), 10)
+	case uint64:
+		return strconv.FormatUint(x, 10)
+	case float32:
+		return strconv.FormatFloat(float64(x), 'f', -1, 32)
+	case float64:
+		return strconv.FormatFloat(x, 'f', -1, 64)
+	case arrow.Timestamp:
+		return x.ToTime(arrow.Microsecond).UTC().Format("2006-01-02T15:04:05.000000Z")
+	case []any:
+		parts := make([]string, len(x))
+		for i, e := range x {
+			parts[i] = formatValue(e)
+		}
+		return strings.Join(parts, ",")
+	}
+	return fmt.Sprint(v)
+}
@@ -0,0 +1,354 @@
+package main
+
+import (
+	"encoding/binary"
+	"fmt"
+	"io"
+	"io/fs"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/apache/arrow/go/v14/arrow"
+	"github.com/apache/arrow/go/v14/parquet/file"
+	"github.com/apache/arrow/go/v14/parquet/metadata"
+)
+
+//
+// ---------------- DATASET ----------------
+//
+
+// DatasetOptions vem de [output.dataset]. O dataset é particionado no
+// estilo Hive: <root>/datagroup=<nome>/date=<AAAA-MM-DD>/sac_sic=<SAC_SIC>/part-<stem>-<id>.parquet,
+// com id derivado do caminho da captura.
+type DatasetOptions struct {
+	Root       string   `toml:"root"`       // diretório do dataset (padrão "dataset")
+	Partitions []string `toml:"partitions"` // date, sac_sic ou um label (padrão ["date", "sac_sic"])
+}
+
+func (o DatasetOptions) merge(over DatasetOptions) DatasetOptions {
+	if over.Root != "" {
+		o.Root = over.Root
+	}
+	if over.Partitions != nil {
+		o.Partitions = over.Partitions
+	}
+	return o
+}
+
+func (o DatasetOptions) root() string {
+	if o.Root == "" {
+		return "dataset"
+	}
+	return o.Root
+}
+
+func (o DatasetOptions) partitions() []string {
+	if o.Partitions == nil {
+		return []string{"date", "sac_sic"}
+	}
+	return o.Partitions
+}
+
+// valor de partição para null, como no Hive
+const hiveDefaultPartition = "__HIVE_DEFAULT_PARTITION__"
+
+// datasetDirs guarda os diretórios datagroup=<nome> gravados na execução,
+// para gerar o _metadata de cada um no fim.
+var datasetDirs = struct {
+	sync.Mutex
+	m map[string]bool
+}{m: map[string]bool{}}
+
+// partitionKey extrai o valor de uma partição da linha.
+type partitionKey struct {
+	name string
+	cols []int // date: tempo; sac_sic: SAC e SIC; label: a coluna
+}
+
+// DatasetWriter distribui as linhas de um arquivo entre as partições,
+// abrindo um ParquetWriter por partição na primeira linha que chega nela.
+type DatasetWriter struct {
+	dir   string // <root>/datagroup=<nome>
+	part  string // part-<stem>-<id>.parquet
+	id    string // sourceID da captura
+	keys  []partitionKey
+	cols  []DataItem
+	opts  ParquetOptions
+	parts map[string]*ParquetWriter
+	path  []string
+}
+
+func newDatasetWriter(t sinkTarget) (*DatasetWriter, error) {
+	opts := t.Output.Dataset
+	id := sourceID(t.Source)
+	w := &DatasetWriter{
+		dir:   filepath.Join(opts.root(), "datagroup="+hiveEscape(t.Name)),
+		part:  "part-" + t.Stem + "-" + id + ".parquet",
+		id:    id,
+		cols:  t.Fields,
+		opts:  t.Output.Parquet,
+		parts: map[string]*ParquetWriter{},
+	}
+
+	for _, name := range opts.partitions() {
+		k := partitionKey{name: name}
+		switch name {
+		case "date":
+			if i := timeColumn(t.Fields); i >= 0 {
+				k.cols = []int{i}
+			}
+		case "sac_sic":
+			if sac, sic := sacSicColumns(t.Fields); sac >= 0 && sic >= 0 {
+				k.cols = []int{sac, sic}
+			}
+		default:
+			for i, f := range t.Fields {
+				if f.Label == name {
+					k.cols = []int{i}
+				}
+			}
+			if k.cols == nil {
+				return nil, fmt.Errorf("partição %q não é date, sac_sic nem um label do datagroup", name)
+			}
+		}
+		w.keys = append(w.keys, k)
+	}
+
+	if err := os.MkdirAll(w.dir, 0o755); err != nil {
+		return nil, err
+	}
+	datasetDirs.Lock()
+	datasetDirs.m[w.dir] = true
+	datasetDirs.Unlock()
+	return w, nil
+}
+
+// timeColumn é a coluna de frame.time_epoch ou, sem ela, o primeiro timestamp.
+func timeColumn(fields []DataItem) int {
+	for i, f := range fields {
+		if f.Field == "frame.time_epoch" {
+			return i
+		}
+	}
+	for i, f := range fields {
+		if f.Type == "timestamp" {
+			return i
+		}
+	}
+	return -1
+}
+
+func (w *DatasetWriter) Paths() []string {
+	return []string{w.dir}
+}
+
+func (w *DatasetWriter) WriteRow(values []any) error {
+	w.path = w.path[:0]
+	for _, k := range w.keys {
+		w.path = append(w.path, k.name+"="+hiveEscape(k.value(values)))
+	}
+	dir := filepath.Join(w.path...)
+
+	pw, ok := w.parts[dir]
+	if !ok {
+		full := filepath.Join(w.dir, dir)
+		if err := os.MkdirAll(full, 0o755); err != nil {
+			return err
+		}
+		var err error
+		if pw, err = newParquetWriter(filepath.Join(full, w.part), w.cols, w.opts); err != nil {
+			return err
+		}
+		w.parts[dir] = pw
+	}
+	return pw.WriteRow(values)
+}
+
+func (w *DatasetWriter) Close() error {
+	var first error
+	for _, pw := range w.parts {
+		if err := pw.Close(); err != nil && first == nil {
+			first = err
+		}
+	}
+	if first != nil {
+		return first
+	}
+	return w.removeStale()
+}
+
+// removeStale apaga os parts de uma conversão anterior da mesma captura que
+// não foram regravados, como os de partições onde ela não tem mais linhas.
+func (w *DatasetWriter) removeStale() error {
+	written := map[string]bool{}
+	for dir := range w.parts {
+		written[filepath.Join(w.dir, dir, w.part)] = true
+	}
+	suffix := "-" + w.id + ".parquet"
+	return filepath.WalkDir(w.dir, func(path string, d fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		name := d.Name()
+		if d.IsDir() || written[path] || !strings.HasPrefix(name, "part-") || !strings.HasSuffix(name, suffix) {
+			return nil
+		}
+		return os.Remove(path)
+	})
+}
+
+func (w *DatasetWriter) Abort() {
+	for _, pw := range w.parts {
+		pw.Abort()
+	}
+}
+
+// value retorna o valor da partição; sem coluna ou com null, o padrão do Hive.
+func (k partitionKey) value(values []any) string {
+	if k.cols == nil {
+		return hiveDefaultPartition
+	}
+	switch k.name {
+	case "date":
+		t, ok := rowTime(values[k.cols[0]])
+		if !ok {
+			return hiveDefaultPartition
+		}
+		return t.UTC().Format("2006-01-02")
+	case "sac_sic":
+		sac, sic := values[k.cols[0]], values[k.cols[1]]
+		if sac == nil || sic == nil {
+			return hiveDefaultPartition
+		}
+		return formatValue(sac) + "_" + formatValue(sic)
+	}
+	if values[k.cols[0]] == nil {
+		return hiveDefaultPartition
+	}
+	return formatValue(values[k.cols[0]])
+}
+
+// rowTime interpreta um valor de tempo convertido: timestamp, segundos
+// desde a época ou texto.
+func rowTime(v any) (time.Time, bool) {
+	switch x := v.(type) {
+	case arrow.Timestamp:
+		return x.ToTime(arrow.Microsecond), true
+	case float64:
+		return time.UnixMicro(int64(x * 1e6)), true
+	case float32:
+		return time.UnixMicro(int64(float64(x) * 1e6)), true
+	case int64:
+		return time.Unix(x, 0), true
+	case string:
+		ts, err := parseTimestamp(x)
+		if err != nil {
+			return time.Time{}, false
+		}
+		return ts.ToTime(arrow.Microsecond), true
+	}
+	return time.Time{}, false
+}
+
+// hiveEscape codifica os caracteres que não podem aparecer num valor de
+// partição, como o Hive faz (%XX).
+func hiveEscape(s string) string {
+	var b strings.Builder
+	for i := 0; i < len(s); i++ {
+		c := s[i]
+		if c < 0x20 || strings.IndexByte(`"#%'*/:=?\{[]^`, c) >= 0 {
+			fmt.Fprintf(&b, "%%%02X", c)
+			continue
+		}
+		b.WriteByte(c)
+	}
+	return b.String()
+}
+
+// finishDatasets grava o _metadata de cada datagroup escrito na execução,
+// reunindo os footers de todos os part-*.parquet do diretório, para que
+// Spark/DuckDB/Polars leiam o dataset como uma tabela.
+func finishDatasets() error {
+	datasetDirs.Lock()
+	defer datasetDirs.Unlock()
+
+	dirs := make([]string, 0, len(datasetDirs.m))
+	for dir := range datasetDirs.m {
+		dirs = append(dirs, dir)
+	}
+	sort.Strings(dirs)
+
+	for _, dir := range dirs {
+		if err := writeDatasetMetadata(dir); err != nil {
+			return fmt.Errorf("%s: %w", dir, err)
+		}
+		delete(datasetDirs.m, dir)
+	}
+	return nil
+}
+
+func writeDatasetMetadata(dir string) error {
+	var parts []string
+	err := filepath.WalkDir(dir, func(path string, d fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		if !d.IsDir() && strings.HasPrefix(d.Name(), "part-") && strings.HasSuffix(d.Name(), ".parquet") {
+			parts = append(parts, path)
+		}
+		return nil
+	})
+	if err != nil || len(parts) == 0 {
+		return err
+	}
+	sort.Strings(parts)
+
+	var summary *metadata.FileMetaData
+	for _, path := range parts {
+		rel, err := filepath.Rel(dir, path)
+		if err != nil {
+			return err
+		}
+		r, err := file.OpenParquetFile(path, false)
+		if err != nil {
+			return fmt.Errorf("%s: %w", rel, err)
+		}
+		md := r.MetaData()
+		md.SetFilePath(filepath.ToSlash(rel))
+		r.Close()
+
+		if summary == nil {
+			summary = md
+			continue
+		}
+		if err := summary.AppendRowGroups(md); err != nil {
+			return fmt.Errorf("%s: schema diferente: %w", rel, err)
+		}
+	}
+
+	out, err := createOutput(filepath.Join(dir, "_metadata"))
+	if err != nil {
+		return err
+	}
+	return out.finish(writeFooter(out, summary))
+}
+
+// writeFooter grava um parquet sem dados: magic, footer, tamanho, magic.
+func writeFooter(w io.Writer, md *metadata.FileMetaData) error {
+	if _, err := io.WriteString(w, "PAR1"); err != nil {
+		return err
+	}
+	n, err := md.WriteTo(w, nil)
+	if err != nil {
+		return err
+	}
+	if err := binary.Write(w, binary.LittleEndian, uint32(n)); err != nil {
+		return err
+	}
+	_, err = io.WriteString(w, "PAR1")
+	return err
+}
@@ -0,0 +1,113 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"io"
+	"path/filepath"
+	"sort"
+	"text/tabwriter"
+	"time"
+)
+
+//
+// ---------------- ERROS ----------------
+//
+
+// códigos de saída do processo
+const (
+	exitOK     = 0 // todas as capturas convertidas (ou já convertidas)
+	exitFailed = 1 // alguma captura ou saída compartilhada falhou
+	exitConfig = 2 // configuração ou argumentos inválidos; nada foi processado
+)
+
+// etapas da conversão de uma captura
+const (
+	StageOpen    = "abertura das saídas"
+	StageRead    = "leitura"
+	StageConvert = "conversão"
+	StageWrite   = "gravação"
+	StageClose   = "fechamento"
+)
+
+// FileError é a falha de uma captura, com a etapa e o datagroup em que
+// ocorreu.
+type FileError struct {
+	File  string
+	Group string // vazio quando não é de um datagroup
+	Stage string
+	Err   error
+}
+
+func (e *FileError) Error() string {
+	msg := filepath.Base(e.File)
+	if e.Group != "" {
+		msg += " [" + e.Group + "]"
+	}
+	return fmt.Sprintf("%s: %s: %v", msg, e.Stage, e.Err)
+}
+
+func (e *FileError) Unwrap() error {
+	return e.Err
+}
+
+// status de uma captura no resumo
+const (
+	StatusOK      = "ok"
+	StatusSkipped = "ignorado" // todas as saídas já existiam (exists = skip)
+	StatusFailed  = "falhou"
+)
+
+// fileResult é o resultado da conversão de uma captura.
+type fileResult struct {
+	file     string
+	status   string
+	err      error               // o primeiro erro; os demais só são impressos
+	outputs  map[string][]string // por datagroup
+	rows     map[string]int
+	source   sourceState // conteúdo lido, para o manifesto
+	duration time.Duration
+}
+
+// fail registra o erro, mantendo o primeiro.
+func (r *fileResult) fail(err error) {
+	fmt.Println("❌", err)
+	r.status = StatusFailed
+	if r.err == nil {
+		r.err = err
+	}
+}
+
+// printSummary imprime uma linha por captura e retorna quantas falharam.
+func printSummary(w io.Writer, results []fileResult, upToDate int) int {
+	sort.Slice(results, func(i, j int) bool { return results[i].file < results[j].file })
+
+	tw := tabwriter.NewWriter(w, 0, 0, 2, ' ', 0)
+	fmt.Fprintln(tw, "arquivo\tstatus\tlinhas\ttempo\terro")
+	count := map[string]int{}
+	for _, r := range results {
+		count[r.status]++
+		rows := 0
+		for _, n := range r.rows {
+			rows += n
+		}
+		// o nome do arquivo já está na primeira coluna
+		msg := ""
+		var fe *FileError
+		switch {
+		case errors.As(r.err, &fe):
+			msg = fe.Stage + ": " + fe.Err.Error()
+			if fe.Group != "" {
+				msg = "[" + fe.Group + "] " + msg
+			}
+		case r.err != nil:
+			msg = r.err.Error()
+		}
+		fmt.Fprintf(tw, "%s\t%s\t%d\t%.1fs\t%s\n", r.file, r.status, rows, r.duration.Seconds(), msg)
+	}
+	tw.Flush()
+
+	fmt.Fprintf(w, "%d ok, %d ignorados, %d já convertidos, %d com falha\n",
+		count[StatusOK], count[StatusSkipped], upToDate, count[StatusFailed])
+	return count[StatusFailed]
+}
@@ -0,0 +1,880 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"math"
+	"strconv"
+	"strings"
+	"unicode"
+	"unicode/utf8"
+
+	"github.com/apache/arrow/go/v14/arrow"
+)
+
+//
+// ---------------- EXPRESSÕES ----------------
+//
+
+// Expr é uma expressão compilada sobre os labels de uma linha, ex.
+// "hypot(VX, VY) * 1.94384" ou "NACP >= 8 ? 'ok' : 'baixo'".
+//
+// Valores: nil (null), bool, int64, float64 e string. Operações com null
+// resultam em null; em condições, null é falso.
+type Expr struct {
+	src  string
+	root exprNode
+	refs []string
+}
+
+type exprNode interface {
+	eval(env map[string]any) (any, error)
+}
+
+func compileExpr(src string) (*Expr, error) {
+	p := &exprParser{lex: exprLexer{src: src}}
+	p.next()
+	root, err := p.parse(0)
+	if err == nil && p.tok.kind != tokEOF {
+		err = p.errorf("token inesperado %q", p.tok.text)
+	}
+	if p.lex.err != nil {
+		// o erro do léxico explica o EOF que o parser viu
+		err = p.lex.err
+	}
+	if err != nil {
+		return nil, fmt.Errorf("expressão %q: %w", src, err)
+	}
+	return &Expr{src: src, root: root, refs: p.refs}, nil
+}
+
+// Refs lista os labels usados pela expressão.
+func (e *Expr) Refs() []string {
+	return e.refs
+}
+
+func (e *Expr) Eval(env map[string]any) (any, error) {
+	return e.root.eval(env)
+}
+
+// exprValue normaliza um valor tipado da linha para os tipos da expressão.
+func exprValue(v any) any {
+	switch x := v.(type) {
+	case int8:
+		return int64(x)
+	case int16:
+		return int64(x)
+	case int32:
+		return int64(x)
+	case uint8:
+		return int64(x)
+	case uint16:
+		return int64(x)
+	case uint32:
+		return int64(x)
+	case uint64:
+		return int64(x)
+	case float32:
+		// evita 0.1 virar 0.10000000149011612
+		f, _ := strconv.ParseFloat(strconv.FormatFloat(float64(x), 'g', -1, 32), 64)
+		return f
+	case arrow.Timestamp:
+		return float64(x) / 1e6
+	}
+	return v
+}
+
+// castValue converte o resultado para o tipo da coluna, com as mesmas
+// regras de conversão dos campos lidos da captura.
+func castValue(t string, v any) (any, error) {
+	if v == nil {
+		return nil, nil
+	}
+	return parseTyped(t, formatExprValue(v))
+}
+
+func formatExprValue(v any) string {
+	switch x := v.(type) {
+	case int64:
+		return strconv.FormatInt(x, 10)
+	case float64:
+		return strconv.FormatFloat(x, 'f', -1, 64)
+	case bool:
+		return strconv.FormatBool(x)
+	case string:
+		return x
+	}
+	return fmt.Sprint(v)
+}
+
+//
+// léxico
+//
+
+type tokKind int
+
+const (
+	tokEOF tokKind = iota
+	tokNum
+	tokStr
+	tokIdent
+	tokOp
+)
+
+type exprToken struct {
+	kind tokKind
+	text string
+	pos  int
+}
+
+type exprLexer struct {
+	src string
+	pos int
+	err error
+}
+
+// operadores de dois caracteres, testados antes dos de um
+var exprOps2 = []string{"**", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||"}
+
+func (l *exprLexer) next() exprToken {
+	for l.pos < len(l.src) && unicode.IsSpace(rune(l.src[l.pos])) {
+		l.pos++
+	}
+	start := l.pos
+	if l.pos >= len(l.src) {
+		return exprToken{kind: tokEOF, pos: start}
+	}
+
+	c := l.src[l.pos]
+	r, _ := utf8.DecodeRuneInString(l.src[l.pos:]) // labels podem ter acentos
+	switch {
+	case c >= '0' && c <= '9' || c == '.' && l.pos+1 < len(l.src) && isDigit(l.src[l.pos+1]):
+		if strings.HasPrefix(l.src[l.pos:], "0x") || strings.HasPrefix(l.src[l.pos:], "0X") {
+			l.pos += 2
+			for l.pos < len(l.src) && isHexDigit(l.src[l.pos]) {
+				l.pos++
+			}
+			return exprToken{kind: tokNum, text: l.src[start:l.pos], pos: start}
+		}
+		for l.pos < len(l.src) && (isDigit(l.src[l.pos]) || l.src[l.pos] == '.') {
+			l.pos++
+		}
+		if l.pos < len(l.src) && (l.src[l.pos] == 'e' || l.src[l.pos] == 'E') {
+			l.pos++
+			if l.pos < len(l.src) && (l.src[l.pos] == '+' || l.src[l.pos] == '-') {
+				l.pos++
+			}
+			for l.pos < len(l.src) && isDigit(l.src[l.pos]) {
+				l.pos++
+			}
+		}
+		return exprToken{kind: tokNum, text: l.src[start:l.pos], pos: start}
+
+	case c == '\'' || c == '"' || c == '`':
+		// `...` é um label com caracteres especiais, ex. `TIME_MSG_RX(s)`
+		end := strings.IndexByte(l.src[l.pos+1:], c)
+		if end < 0 {
+			l.err = fmt.Errorf("posição %d: texto sem fechamento", start)
+			l.pos = len(l.src)
+			return exprToken{kind: tokEOF, pos: start}
+		}
+		text := l.src[l.pos+1 : l.pos+1+end]
+		l.pos += end + 2
+		if c == '`' {
+			return exprToken{kind: tokIdent, text: text, pos: start}
+		}
+		return exprToken{kind: tokStr, text: text, pos: start}
+
+	case r == '_' || unicode.IsLetter(r):
+		for l.pos < len(l.src) {
+			r, size := utf8.DecodeRuneInString(l.src[l.pos:])
+			if r != '_' && r != '.' && !unicode.IsLetter(r) && !(r < utf8.RuneSelf && isDigit(byte(r))) {
+				break
+			}
+			l.pos += size
+		}
+		return exprToken{kind: tokIdent, text: l.src[start:l.pos], pos: start}
+	}
+
+	for _, op := range exprOps2 {
+		if strings.HasPrefix(l.src[l.pos:], op) {
+			l.pos += 2
+			return exprToken{kind: tokOp, text: op, pos: start}
+		}
+	}
+	if strings.IndexByte("+-*/%()<>!~&|^?:,", c) >= 0 {
+		l.pos++
+		return exprToken{kind: tokOp, text: string(c), pos: start}
+	}
+	l.err = fmt.Errorf("posição %d: caractere inesperado %q", start, r)
+	l.pos = len(l.src)
+	return exprToken{kind: tokEOF, pos: start}
+}
+
+func isDigit(c byte) bool {
+	return c >= '0' && c <= '9'
+}
+
+func isHexDigit(c byte) bool {
+	return isDigit(c) || c >= 'a' && c <= 'f' || c >= 'A' && c <= 'F'
+}
+
+//
+// sintaxe (Pratt)
+//
+
+// binaryPrec retorna a precedência do operador binário, ou 0 se não for
+// um. ** associa à direita.
+func binaryPrec(op string) int {
+	switch op {
+	case "?":
+		return 1
+	case "||":
+		return 2
+	case "&&":
+		return 3
+	case "|":
+		return 4
+	case "^":
+		return 5
+	case "&":
+		return 6
+	case "==", "!=":
+		return 7
+	case "<", "<=", ">", ">=":
+		return 8
+	case "<<", ">>":
+		return 9
+	case "+", "-":
+		return 10
+	case "*", "/", "%":
+		return 11
+	case "**":
+		return 13
+	}
+	return 0
+}
+
+const precUnary = 12
+
+type exprParser struct {
+	lex  exprLexer
+	tok  exprToken
+	refs []string
+}
+
+func (p *exprParser) next() {
+	p.tok = p.lex.next()
+}
+
+func (p *exprParser) errorf(format string, args ...any) error {
+	return fmt.Errorf("posição %d: %s", p.tok.pos, fmt.Sprintf(format, args...))
+}
+
+func (p *exprParser) expect(op string) error {
+	if p.tok.kind != tokOp || p.tok.text != op {
+		return p.errorf("esperado %q", op)
+	}
+	p.next()
+	return nil
+}
+
+func (p *exprParser) parse(minPrec int) (exprNode, error) {
+	left, err := p.unary()
+	if err != nil {
+		return nil, err
+	}
+	for p.tok.kind == tokOp {
+		op := p.tok.text
+		prec := binaryPrec(op)
+		if prec == 0 || prec < minPrec {
+			break
+		}
+		p.next()
+
+		if op == "?" {
+			a, err := p.parse(0)
+			if err != nil {
+				return nil, err
+			}
+			if err := p.expect(":"); err != nil {
+				return nil, err
+			}
+			b, err := p.parse(prec)
+			if err != nil {
+				return nil, err
+			}
+			left = &condNode{left, a, b}
+			continue
+		}
+
+		next := prec + 1
+		if op == "**" {
+			next = prec
+		}
+		right, err := p.parse(next)
+		if err != nil {
+			return nil, err
+		}
+		left = &binaryNode{op, left, right}
+	}
+	return left, nil
+}
+
+func (p *exprParser) unary() (exprNode, error) {
+	if p.tok.kind == tokOp && (p.tok.text == "-" || p.tok.text == "!" || p.tok.text == "~" || p.tok.text == "+") {
+		op := p.tok.text
+		p.next()
+		x, err := p.parse(precUnary)
+		if err != nil {
+			return nil, err
+		}
+		if op == "+" {
+			return x, nil
+		}
+		return &unaryNode{op, x}, nil
+	}
+	return p.primary()
+}
+
+func (p *exprParser) primary() (exprNode, error) {
+	tok := p.tok
+	switch tok.kind {
+	case tokNum:
+		p.next()
+		v, err := parseExprNumber(tok.text)
+		if err != nil {
+			return nil, fmt.Errorf("posição %d: número inválido %q", tok.pos, tok.text)
+		}
+		return constNode{v}, nil
+
+	case tokStr:
+		p.next()
+		return constNode{tok.text}, nil
+
+	case tokIdent:
+		p.next()
+		if p.tok.kind == tokOp && p.tok.text == "(" {
+			return p.call(tok)
+		}
+		switch tok.text {
+		case "true":
+			return constNode{true}, nil
+		case "false":
+			return constNode{false}, nil
+		case "null":
+			return constNode{nil}, nil
+		}
+		p.refs = append(p.refs, tok.text)
+		return refNode(tok.text), nil
+
+	case tokOp:
+		if tok.text == "(" {
+			p.next()
+			x, err := p.parse(0)
+			if err != nil {
+				return nil, err
+			}
+			return x, p.expect(")")
+		}
+	}
+	if tok.kind == tokEOF {
+		return nil, p.errorf("expressão incompleta")
+	}
+	return nil, p.errorf("token inesperado %q", tok.text)
+}
+
+func (p *exprParser) call(name exprToken) (exprNode, error) {
+	p.next() // (
+	var args []exprNode
+	for !(p.tok.kind == tokOp && p.tok.text == ")") {
+		if len(args) > 0 {
+			if err := p.expect(","); err != nil {
+				return nil, err
+			}
+		}
+		a, err := p.parse(0)
+		if err != nil {
+			return nil, err
+		}
+		args = append(args, a)
+	}
+	p.next() // )
+
+	if name.text == "if" {
+		if len(args) != 3 {
+			return nil, fmt.Errorf("posição %d: if espera 3 argumentos", name.pos)
+		}
+		return &condNode{args[0], args[1], args[2]}, nil
+	}
+	fn, ok := exprFuncs[name.text]
+	if !ok {
+		return nil, fmt.Errorf("posição %d: função desconhecida %s", name.pos, name.text)
+	}
+	if len(args) < fn.min || fn.max >= 0 && len(args) > fn.max {
+		return nil, fmt.Errorf("posição %d: número de argumentos inválido para %s", name.pos, name.text)
+	}
+	return &callNode{name.text, fn, args}, nil
+}
+
+func parseExprNumber(s string) (any, error) {
+	if digits, base := hexDigits(s); base == 16 {
+		x, err := strconv.ParseUint(digits, 16, 64)
+		return int64(x), err
+	}
+	if i, err := strconv.ParseInt(s, 10, 64); err == nil {
+		return i, nil
+	}
+	return strconv.ParseFloat(s, 64)
+}
+
+//
+// avaliação
+//
+
+type constNode struct{ v any }
+
+func (n constNode) eval(map[string]any) (any, error) {
+	return n.v, nil
+}
+
+type refNode string
+
+func (n refNode) eval(env map[string]any) (any, error) {
+	return env[string(n)], nil
+}
+
+type condNode struct{ cond, a, b exprNode }
+
+func (n *condNode) eval(env map[string]any) (any, error) {
+	c, err := n.cond.eval(env)
+	if err != nil {
+		return nil, err
+	}
+	if truthy(c) {
+		return n.a.eval(env)
+	}
+	return n.b.eval(env)
+}
+
+type unaryNode struct {
+	op string
+	x  exprNode
+}
+
+func (n *unaryNode) eval(env map[string]any) (any, error) {
+	v, err := n.x.eval(env)
+	if err != nil || v == nil {
+		return nil, err
+	}
+	switch n.op {
+	case "!":
+		return !truthy(v), nil
+	case "~":
+		i, err := toInt(v)
+		return ^i, err
+	}
+	switch x := v.(type) {
+	case int64:
+		return -x, nil
+	case float64:
+		return -x, nil
+	}
+	return nil, fmt.Errorf("operador - inválido para %T", v)
+}
+
+type binaryNode struct {
+	op   string
+	l, r exprNode
+}
+
+func (n *binaryNode) eval(env map[string]any) (any, error) {
+	l, err := n.l.eval(env)
+	if err != nil {
+		return nil, err
+	}
+
+	// && e || avaliam o lado direito só quando necessário
+	switch n.op {
+	case "&&":
+		if !truthy(l) {
+			return false, nil
+		}
+		r, err := n.r.eval(env)
+		return truthy(r), err
+	case "||":
+		if truthy(l) {
+			return true, nil
+		}
+		r, err := n.r.eval(env)
+		return truthy(r), err
+	}
+
+	r, err := n.r.eval(env)
+	if err != nil || l == nil || r == nil {
+		return nil, err
+	}
+
+	switch n.op {
+	case "==", "!=", "<", "<=", ">", ">=":
+		c, err := compareValues(l, r)
+		if err != nil {
+			return nil, err
+		}
+		switch n.op {
+		case "==":
+			return c == 0, nil
+		case "!=":
+			return c != 0, nil
+		case "<":
+			return c < 0, nil
+		case "<=":
+			return c <= 0, nil
+		case ">":
+			return c > 0, nil
+		}
+		return c >= 0, nil
+
+	case "&", "|", "^", "<<", ">>":
+		a, err := toInt(l)
+		if err != nil {
+			return nil, err
+		}
+		b, err := toInt(r)
+		if err != nil {
+			return nil, err
+		}
+		switch n.op {
+		case "&":
+			return a & b, nil
+		case "|":
+			return a | b, nil
+		case "^":
+			return a ^ b, nil
+		case "<<":
+			return a << uint64(b), nil
+		}
+		return int64(uint64(a) >> uint64(b)), nil
+	}
+
+	if ls, ok := l.(string); ok && n.op == "+" {
+		if rs, ok := r.(string); ok {
+			return ls + rs, nil
+		}
+	}
+
+	// inteiros ficam inteiros, exceto na divisão e na potência
+	if a, ok := l.(int64); ok {
+		if b, ok := r.(int64); ok {
+			switch n.op {
+			case "+":
+				return a + b, nil
+			case "-":
+				return a - b, nil
+			case "*":
+				return a * b, nil
+			case "%":
+				if b == 0 {
+					return nil, nil
+				}
+				return a % b, nil
+			}
+		}
+	}
+
+	a, err := toFloat(l)
+	if err != nil {
+		return nil, err
+	}
+	b, err := toFloat(r)
+	if err != nil {
+		return nil, err
+	}
+	switch n.op {
+	case "+":
+		return a + b, nil
+	case "-":
+		return a - b, nil
+	case "*":
+		return a * b, nil
+	case "/":
+		if b == 0 {
+			return nil, nil
+		}
+		return a / b, nil
+	case "%":
+		if b == 0 {
+			return nil, nil
+		}
+		return math.Mod(a, b), nil
+	case "**":
+		return math.Pow(a, b), nil
+	}
+	return nil, fmt.Errorf("operador desconhecido %s", n.op)
+}
+
+type exprFunc struct {
+	min, max int // max < 0: sem limite
+	fn       func(args []any) (any, error)
+}
+
+type callNode struct {
+	name string
+	fn   exprFunc
+	args []exprNode
+}
+
+func (n *callNode) eval(env map[string]any) (any, error) {
+	args := make([]any, len(n.args))
+	for i, a := range n.args {
+		v, err := a.eval(env)
+		if err != nil {
+			return nil, err
+		}
+		args[i] = v
+	}
+	v, err := n.fn.fn(args)
+	if err != nil {
+		return nil, fmt.Errorf("%s: %w", n.name, err)
+	}
+	return v, nil
+}
+
+var exprFuncs = map[string]exprFunc{
+	"abs": {1, 1, func(a []any) (any, error) {
+		if i, ok := a[0].(int64); ok {
+			if i < 0 {
+				return -i, nil
+			}
+			return i, nil
+		}
+		return mathFunc(math.Abs)(a)
+	}},
+	"sqrt":  {1, 1, mathFunc(math.Sqrt)},
+	"floor": {1, 1, mathFunc(math.Floor)},
+	"ceil":  {1, 1, mathFunc(math.Ceil)},
+	"round": {1, 1, mathFunc(math.Round)},
+	"trunc": {1, 1, mathFunc(math.Trunc)},
+	"exp":   {1, 1, mathFunc(math.Exp)},
+	"log":   {1, 1, mathFunc(math.Log)},
+	"log10": {1, 1, mathFunc(math.Log10)},
+	"sin":   {1, 1, mathFunc(math.Sin)},
+	"cos":   {1, 1, mathFunc(math.Cos)},
+	"tan":   {1, 1, mathFunc(math.Tan)},
+	"asin":  {1, 1, mathFunc(math.Asin)},
+	"acos":  {1, 1, mathFunc(math.Acos)},
+	"atan":  {1, 1, mathFunc(math.Atan)},
+	"deg":   {1, 1, mathFunc(func(x float64) float64 { return x * 180 / math.Pi })},
+	"rad":   {1, 1, mathFunc(func(x float64) float64 { return x * math.Pi / 180 })},
+	"atan2": {2, 2, mathFunc2(math.Atan2)},
+	"hypot": {2, 2, mathFunc2(math.Hypot)},
+	"pow":   {2, 2, mathFunc2(math.Pow)},
+	"min":   {1, -1, func(a []any) (any, error) { return pick(a, -1) }},
+	"max":   {1, -1, func(a []any) (any, error) { return pick(a, 1) }},
+
+	// bits(x, lsb, n): n bits de x a partir do bit lsb (0 = menos significativo)
+	"bits": {3, 3, func(a []any) (any, error) {
+		if a[0] == nil || a[1] == nil || a[2] == nil {
+			return nil, nil
+		}
+		var v [3]int64
+		for i := range v {
+			x, err := toInt(a[i])
+			if err != nil {
+				return nil, err
+			}
+			v[i] = x
+		}
+		if v[1] < 0 || v[2] < 0 || v[2] > 63 {
+			return nil, errors.New("faixa de bits inválida")
+		}
+		return int64(uint64(v[0])>>uint64(v[1])) & (1<<uint64(v[2]) - 1), nil
+	}},
+
+	"isnull": {1, 1, func(a []any) (any, error) { return a[0] == nil, nil }},
+
+	"coalesce": {1, -1, func(a []any) (any, error) {
+		for _, v := range a {
+			if v != nil {
+				return v, nil
+			}
+		}
+		return nil, nil
+	}},
+	"int": {1, 1, func(a []any) (any, error) {
+		if a[0] == nil {
+			return nil, nil
+		}
+		return toInt(a[0])
+	}},
+	"float": {1, 1, func(a []any) (any, error) {
+		if a[0] == nil {
+			return nil, nil
+		}
+		return toFloat(a[0])
+	}},
+	"str": {1, 1, func(a []any) (any, error) {
+		if a[0] == nil {
+			return nil, nil
+		}
+		return formatExprValue(a[0]), nil
+	}},
+	"len": {1, 1, func(a []any) (any, error) {
+		switch x := a[0].(type) {
+		case string:
+			return int64(len(x)), nil
+		case []any:
+			return int64(len(x)), nil
+		}
+		return nil, nil
+	}},
+}
+
+func mathFunc(f func(float64) float64) func([]any) (any, error) {
+	return func(a []any) (any, error) {
+		if a[0] == nil {
+			return nil, nil
+		}
+		x, err := toFloat(a[0])
+		if err != nil {
+			return nil, err
+		}
+		return f(x), nil
+	}
+}
+
+func mathFunc2(f func(float64, float64) float64) func([]any) (any, error) {
+	return func(a []any) (any, error) {
+		if a[0] == nil || a[1] == nil {
+			return nil, nil
+		}
+		x, err := toFloat(a[0])
+		if err != nil {
+			return nil, err
+		}
+		y, err := toFloat(a[1])
+		if err != nil {
+			return nil, err
+		}
+		return f(x, y), nil
+	}
+}
+
+// pick retorna o menor (sign < 0) ou o maior valor, ignorando nulls.
+func pick(a []any, sign int) (any, error) {
+	var best any
+	for _, v := range a {
+		if v == nil {
+			continue
+		}
+		if best == nil {
+			best = v
+			continue
+		}
+		c, err := compareValues(v, best)
+		if err != nil {
+			return nil, err
+		}
+		if c*sign > 0 {
+			best = v
+		}
+	}
+	return best, nil
+}
+
+func truthy(v any) bool {
+	switch x := v.(type) {
+	case bool:
+		return x
+	case int64:
+		return x != 0
+	case float64:
+		return x != 0
+	case string:
+		return x != ""
+	}
+	return false
+}
+
+func toInt(v any) (int64, error) {
+	switch x := v.(type) {
+	case int64:
+		return x, nil
+	case float64:
+		if x != math.Trunc(x) {
+			return 0, fmt.Errorf("%v não é inteiro", x)
+		}
+		return int64(x), nil
+	case bool:
+		if x {
+			return 1, nil
+		}
+		return 0, nil
+	case string:
+		return parseInt(x, 64)
+	}
+	return 0, fmt.Errorf("%T não é número", v)
+}
+
+func toFloat(v any) (float64, error) {
+	switch x := v.(type) {
+	case int64:
+		return float64(x), nil
+	case float64:
+		return x, nil
+	case bool:
+		if x {
+			return 1, nil
+		}
+		return 0, nil
+	case string:
+		return parseFloat(x, 64)
+	}
+	return 0, fmt.Errorf("%T não é número", v)
+}
+
+// compareValues compara textos entre si e números entre si.
+func compareValues(l, r any) (int, error) {
+	ls, lok := l.(string)
+	rs, rok := r.(string)
+	if lok && rok {
+		return strings.Compare(ls, rs), nil
+	}
+	if lb, ok := l.(bool); ok {
+		if rb, ok := r.(bool); ok {
+			if lb == rb {
+				return 0, nil
+			}
+			if rb {
+				return -1, nil
+			}
+			return 1, nil
+		}
+	}
+	if a, ok := l.(int64); ok {
+		if b, ok := r.(int64); ok {
+			switch {
+			case a < b:
+				return -1, nil
+			case a > b:
+				return 1, nil
+			}
+			return 0, nil
+		}
+	}
+	a, err := toFloat(l)
+	if err != nil {
+		return 0, err
+	}
+	b, err := toFloat(r)
+	if err != nil {
+		return 0, err
+	}
+	switch {
+	case a < b:
+		return -1, nil
+	case a > b:
+		return 1, nil
+	}
+	return 0, nil
+}
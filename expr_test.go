@@ -0,0 +1,293 @@
+package main
+
+import (
+	"reflect"
+	"slices"
+	"strings"
+	"testing"
+
+	"github.com/apache/arrow/go/v14/arrow"
+)
+
+func evalExpr(t *testing.T, src string, env map[string]any) any {
+	t.Helper()
+	e, err := compileExpr(src)
+	if err != nil {
+		t.Fatal(err)
+	}
+	v, err := e.Eval(env)
+	if err != nil {
+		t.Fatalf("%s: %v", src, err)
+	}
+	return v
+}
+
+func TestExprEval(t *testing.T) {
+	env := map[string]any{
+		"A":                true,
+		"B":                false,
+		"NACP":             int64(9),
+		"X":                1.5,
+		"TIME(s)":          int64(3),
+		"VELOCIDADE_MÉDIA": 2.5, // label com acento
+	}
+	tests := []struct {
+		src  string
+		want any
+	}{
+		// precedência e associatividade
+		{"1 + 2 * 3", int64(7)},
+		{"(1 + 2) * 3", int64(9)},
+		{"10 - 4 - 3", int64(3)},
+		{"2 ** 3 ** 2", 512.0},
+		{"-2 ** 2", -4.0},
+		{"1 << 2 + 1", int64(8)},
+		{"1 | 2 ^ 3 & 1", int64(3)},
+		{"6 & 3 == 2", int64(0)},
+		{"1 < 2 == true", true},
+		{"true || false && false", true},
+		{"!B && A", true},
+		{"A ? B ? 1 : 2 : 3", int64(2)},
+		{"1 ? 2 : 0 ? 3 : 4", int64(2)},
+		{"NACP >= 8 ? 'ok' : 'baixo'", "ok"},
+		{"if(X > 1, 'a', 'b')", "a"},
+
+		// aritmética
+		{"7 / 2", 3.5},
+		{"7 % 2", int64(1)},
+		{"7.5 % 2", 1.5},
+		{"0x10 + 1", int64(17)},
+		{".5 + 1e1", 10.5},
+		{"-X", -1.5},
+		{"~0", int64(-1)},
+		{"-8 >> 60", int64(15)},
+		{"'AB' + 'C'", "ABC"},
+		{"`TIME(s)` * 2", int64(6)},
+		{"VELOCIDADE_MÉDIA * 2", 5.0},
+
+		// funções
+		{"hypot(3, 4)", 5.0},
+		{"abs(-3)", int64(3)},
+		{"bits(0xF0, 4, 4)", int64(15)},
+		{"min(3, null, 1)", int64(1)},
+		{"max(2.5, 3)", int64(3)},
+		{"len('abc')", int64(3)},
+	}
+	for _, tt := range tests {
+		if got := evalExpr(t, tt.src, env); !reflect.DeepEqual(got, tt.want) {
+			t.Errorf("%s = %#v, quer %#v", tt.src, got, tt.want)
+		}
+	}
+}
+
+func TestExprNull(t *testing.T) {
+	env := map[string]any{"X": nil, "Y": nil, "N": int64(2)}
+	tests := []struct {
+		src  string
+		want any
+	}{
+		{"X + 1", nil},
+		{"FALTA + 1", nil}, // label ausente da linha
+		{"X * 2 > 3", nil},
+		{"X == null", nil},
+		{"-X", nil},
+		{"!X", nil},
+		{"sqrt(X)", nil},
+		{"atan2(N, X)", nil},
+		{"bits(X, 0, 4)", nil},
+		{"int(X)", nil},
+		{"str(X)", nil},
+		{"1 / 0", nil},
+		{"1 % 0", nil},
+		{"N / (N - 2)", nil},
+		{"X ? 1 : 2", int64(2)},
+		{"X && true", false},
+		{"X || N", true},
+		{"isnull(X)", true},
+		{"isnull(N)", false},
+		{"coalesce(X, Y, N)", int64(2)},
+		{"min(X, Y)", nil},
+	}
+	for _, tt := range tests {
+		if got := evalExpr(t, tt.src, env); !reflect.DeepEqual(got, tt.want) {
+			t.Errorf("%s = %#v, quer %#v", tt.src, got, tt.want)
+		}
+	}
+}
+
+func TestExprCompare(t *testing.T) {
+	tests := []struct {
+		src  string
+		want bool
+	}{
+		{"'abc' < 'abd'", true},
+		{"'b' > 'abc'", true},
+		{"'10' > 9", true},
+		{"'0x10' == 16", true},
+		{"2 == 2.0", true},
+		{"2 != 2.5", true},
+		{"true > false", true},
+		{"true == 1", true},
+		{"9007199254740993 > 9007199254740992", true}, // inteiros sem passar por float64
+		{"-1 < 0", true},
+		{"1 >= 1", true},
+		{"1 <= 0.5", false},
+	}
+	for _, tt := range tests {
+		if got := evalExpr(t, tt.src, nil); got != tt.want {
+			t.Errorf("%s = %#v, quer %v", tt.src, got, tt.want)
+		}
+	}
+}
+
+func TestExprCast(t *testing.T) {
+	tests := []struct {
+		src  string
+		want any
+	}{
+		{"int('0x1f')", int64(31)},
+		{"int(2.0)", int64(2)},
+		{"int(true)", int64(1)},
+		{"float('1.5')", 1.5},
+		{"float(true)", 1.0},
+		{"str(1.5)", "1.5"},
+		{"str(3) + 'x'", "3x"},
+		{"str(1e21)", "1000000000000000000000"},
+	}
+	for _, tt := range tests {
+		if got := evalExpr(t, tt.src, nil); !reflect.DeepEqual(got, tt.want) {
+			t.Errorf("%s = %#v, quer %#v", tt.src, got, tt.want)
+		}
+	}
+}
+
+func TestCastValue(t *testing.T) {
+	tests := []struct {
+		typ  string
+		v    any
+		want any
+	}{
+		{"int8", int64(-128), int8(-128)},
+		{"int64", 3.0, int64(3)},
+		{"uint16", int64(65535), uint16(65535)},
+		{"float32", 0.1, float32(0.1)},
+		{"float64", int64(2), 2.0},
+		{"string", int64(5), "5"},
+		{"string", 2.5, "2.5"},
+		{"bool", int64(1), true},
+		{"bool", false, false},
+		{"timestamp", 1700000000.5, arrow.Timestamp(1700000000500000)},
+		{"list<int64>", "1, 2", []any{int64(1), int64(2)}},
+		{"int16", nil, nil},
+	}
+	for _, tt := range tests {
+		got, err := castValue(tt.typ, tt.v)
+		if err != nil {
+			t.Errorf("%s(%#v): %v", tt.typ, tt.v, err)
+			continue
+		}
+		if !reflect.DeepEqual(got, tt.want) {
+			t.Errorf("%s(%#v) = %#v, quer %#v", tt.typ, tt.v, got, tt.want)
+		}
+	}
+
+	// valores que não cabem no type da coluna
+	for _, tt := range []struct {
+		typ string
+		v   any
+	}{
+		{"int8", int64(128)},
+		{"uint8", int64(-1)},
+		{"int32", 2.5},
+		{"bool", int64(2)},
+	} {
+		if got, err := castValue(tt.typ, tt.v); err == nil {
+			t.Errorf("%s(%#v) = %#v, quer erro", tt.typ, tt.v, got)
+		}
+	}
+}
+
+func TestExprValue(t *testing.T) {
+	tests := []struct {
+		v, want any
+	}{
+		{int8(-3), int64(-3)},
+		{uint32(7), int64(7)},
+		{float32(0.1), 0.1},
+		{arrow.Timestamp(1500000), 1.5},
+		{"abc", "abc"},
+		{nil, nil},
+	}
+	for _, tt := range tests {
+		if got := exprValue(tt.v); !reflect.DeepEqual(got, tt.want) {
+			t.Errorf("exprValue(%#v) = %#v, quer %#v", tt.v, got, tt.want)
+		}
+	}
+}
+
+func TestExprCompileErrors(t *testing.T) {
+	tests := []struct {
+		src  string
+		want string
+	}{
+		{"", "posição 0: expressão incompleta"},
+		{"1 +", "posição 3: expressão incompleta"},
+		{"(1 + 2", `posição 6: esperado ")"`},
+		{"1 2", `posição 2: token inesperado "2"`},
+		{"1 ? 2", `posição 5: esperado ":"`},
+		{"X + )", `posição 4: token inesperado ")"`},
+		{"1 $ 2", "posição 2: caractere inesperado '$'"},
+		{"X ° 2", "posição 2: caractere inesperado '°'"},
+		{"LABEL == 'abc", "posição 9: texto sem fechamento"},
+		{"1.2.3 + 1", `posição 0: número inválido "1.2.3"`},
+		{"foo(1)", "posição 0: função desconhecida foo"},
+		{"X + sqrt(1, 2)", "posição 4: número de argumentos inválido para sqrt"},
+		{"min()", "posição 0: número de argumentos inválido para min"},
+		{"if(1, 2)", "posição 0: if espera 3 argumentos"},
+		{"hypot(1 2)", `posição 8: esperado ","`},
+	}
+	for _, tt := range tests {
+		_, err := compileExpr(tt.src)
+		if err == nil {
+			t.Errorf("%q: sem erro", tt.src)
+			continue
+		}
+		if !strings.Contains(err.Error(), tt.want) {
+			t.Errorf("%q: erro %q, quer %q", tt.src, err, tt.want)
+		}
+	}
+}
+
+func TestExprEvalErrors(t *testing.T) {
+	tests := []struct {
+		src  string
+		want string
+	}{
+		{"'a' - 1", "invalid syntax"},
+		{"'abc' == 1", "invalid syntax"},
+		{"-'a'", "operador - inválido"},
+		{"~1.5", "não é inteiro"},
+		{"bits(1, -1, 2)", "bits: faixa de bits inválida"},
+		{"int(2.5)", "int: 2.5 não é inteiro"},
+	}
+	for _, tt := range tests {
+		e, err := compileExpr(tt.src)
+		if err != nil {
+			t.Fatal(err)
+		}
+		_, err = e.Eval(nil)
+		if err == nil || !strings.Contains(err.Error(), tt.want) {
+			t.Errorf("%s: erro %v, quer %q", tt.src, err, tt.want)
+		}
+	}
+}
+
+func TestExprRefs(t *testing.T) {
+	e, err := compileExpr("hypot(VX, VY) * K + `TIME(s)` + (true ? null : X)")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if want := []string{"VX", "VY", "K", "TIME(s)", "X"}; !slices.Equal(e.Refs(), want) {
+		t.Errorf("Refs = %v, quer %v", e.Refs(), want)
+	}
+}
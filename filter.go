@@ -0,0 +1,146 @@
+package main
+
+import (
+	"fmt"
+	"net/netip"
+	"strconv"
+	"strings"
+)
+
+//
+// ---------------- FILTROS ----------------
+//
+
+type sacSic struct{ sac, sic int }
+
+type endpoint struct {
+	prefix netip.Prefix
+	port   int // 0 = qualquer porta
+}
+
+func (e endpoint) match(addr, port string) bool {
+	ip, err := netip.ParseAddr(addr)
+	if err != nil || !e.prefix.Contains(ip) {
+		return false
+	}
+	return e.port == 0 || strconv.Itoa(e.port) == port
+}
+
+// RecordFilter aplica os filtros de um datagroup antes da geração das linhas.
+// Listas vazias não filtram.
+type RecordFilter struct {
+	categories map[int]bool
+	sources    map[sacSic]bool
+	srcIPs     []endpoint
+	srcPorts   map[string]bool
+	groups     []endpoint
+}
+
+func compileFilter(dg Datagroup) (*RecordFilter, error) {
+	f := &RecordFilter{}
+
+	if len(dg.Categories) > 0 {
+		f.categories = map[int]bool{}
+		for _, c := range dg.Categories {
+			f.categories[c] = true
+		}
+	}
+
+	if len(dg.SacSic) > 0 {
+		f.sources = map[sacSic]bool{}
+		for _, s := range dg.SacSic {
+			sac, sic, ok := strings.Cut(s, "/")
+			a, err1 := strconv.Atoi(strings.TrimSpace(sac))
+			b, err2 := strconv.Atoi(strings.TrimSpace(sic))
+			if !ok || err1 != nil || err2 != nil {
+				return nil, fmt.Errorf("sac_sic inválido %q (use \"SAC/SIC\")", s)
+			}
+			f.sources[sacSic{a, b}] = true
+		}
+	}
+
+	for _, s := range dg.SrcIP {
+		e, err := parseEndpoint(s)
+		if err != nil {
+			return nil, fmt.Errorf("src_ip: %w", err)
+		}
+		f.srcIPs = append(f.srcIPs, e)
+	}
+
+	if len(dg.SrcPort) > 0 {
+		f.srcPorts = map[string]bool{}
+		for _, p := range dg.SrcPort {
+			f.srcPorts[strconv.Itoa(p)] = true
+		}
+	}
+
+	for _, s := range dg.Multicast {
+		e, err := parseEndpoint(s)
+		if err != nil {
+			return nil, fmt.Errorf("multicast: %w", err)
+		}
+		f.groups = append(f.groups, e)
+	}
+	return f, nil
+}
+
+// parseEndpoint aceita "ip", "ip/prefixo" ou "ip:porta".
+func parseEndpoint(s string) (endpoint, error) {
+	var e endpoint
+	if ap, err := netip.ParseAddrPort(s); err == nil {
+		e.prefix = netip.PrefixFrom(ap.Addr(), ap.Addr().BitLen())
+		e.port = int(ap.Port())
+		return e, nil
+	}
+	if strings.Contains(s, "/") {
+		p, err := netip.ParsePrefix(s)
+		if err != nil {
+			return e, err
+		}
+		e.prefix = p.Masked()
+		return e, nil
+	}
+	ip, err := netip.ParseAddr(s)
+	if err != nil {
+		return e, err
+	}
+	e.prefix = netip.PrefixFrom(ip, ip.BitLen())
+	return e, nil
+}
+
+// MatchPacket aplica os filtros de rede.
+func (f *RecordFilter) MatchPacket(p Packet) bool {
+	if f.srcPorts != nil && !f.srcPorts[p.Fields["udp.srcport"]] {
+		return false
+	}
+	if len(f.srcIPs) > 0 && !matchAny(f.srcIPs, p.Fields["ip.src"], p.Fields["udp.srcport"]) {
+		return false
+	}
+	if len(f.groups) > 0 && !matchAny(f.groups, p.Fields["ip.dst"], p.Fields["udp.dstport"]) {
+		return false
+	}
+	return true
+}
+
+// MatchRecord aplica os filtros de categoria e SAC/SIC.
+func (f *RecordFilter) MatchRecord(rec Record) bool {
+	if f.categories != nil && !f.categories[rec.Category] {
+		return false
+	}
+	if f.sources != nil {
+		sac, sic, ok := rec.SacSic()
+		if !ok || !f.sources[sacSic{sac, sic}] {
+			return false
+		}
+	}
+	return true
+}
+
+func matchAny(eps []endpoint, addr, port string) bool {
+	for _, e := range eps {
+		if e.match(addr, port) {
+			return true
+		}
+	}
+	return false
+}
@@ -0,0 +1,256 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"compress/gzip"
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"hash"
+	"io"
+	"io/fs"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+
+	"github.com/klauspost/compress/zstd"
+	"github.com/ulikunitz/xz"
+)
+
+//
+// ---------------- ENTRADAS ----------------
+//
+
+// InputOptions vem de [input] e escolhe as capturas lidas de -d.
+type InputOptions struct {
+	Recursive bool     `toml:"recursive"` // inclui subdiretórios; -r liga
+	Include   []string `toml:"include"`   // padrão *.pcap, *.pcapng, *.cap
+	Exclude   []string `toml:"exclude"`   // arquivos ou diretórios ignorados
+}
+
+var defaultInclude = []string{"*.pcap", "*.pcapng", "*.cap"}
+
+// extensões de compressão reconhecidas, retiradas antes dos padrões de include
+var compressedExts = []string{".gz", ".zst", ".xz"}
+
+func (o InputOptions) include() []string {
+	if len(o.Include) == 0 {
+		return defaultInclude
+	}
+	return o.Include
+}
+
+func (o InputOptions) validate() error {
+	for _, p := range append(o.include(), o.Exclude...) {
+		if _, err := filepath.Match(p, ""); err != nil {
+			return fmt.Errorf("padrão inválido %q", p)
+		}
+	}
+	return nil
+}
+
+// matchGlobs testa os padrões contra o nome e contra o caminho relativo, para
+// aceitar tanto "*.pcap" quanto "radar1/*.pcap".
+func matchGlobs(patterns []string, name, rel string) bool {
+	for _, p := range patterns {
+		if ok, _ := filepath.Match(p, name); ok {
+			return true
+		}
+		if ok, _ := filepath.Match(p, rel); ok {
+			return true
+		}
+	}
+	return false
+}
+
+// findInputs lista as capturas de root em ordem, descendo nos
+// subdiretórios com Recursive. include vale para o nome sem a extensão de
+// compressão: "*.pcap" também aceita "x.pcap.gz".
+func findInputs(root string, opts InputOptions) ([]string, error) {
+	var files []string
+	err := filepath.WalkDir(root, func(path string, d fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		if path == root {
+			return nil
+		}
+		rel, _ := filepath.Rel(root, path)
+		rel = filepath.ToSlash(rel)
+		if matchGlobs(opts.Exclude, d.Name(), rel) {
+			if d.IsDir() {
+				return filepath.SkipDir
+			}
+			return nil
+		}
+		if d.IsDir() {
+			if !opts.Recursive {
+				return filepath.SkipDir
+			}
+			return nil
+		}
+		if matchGlobs(opts.include(), trimCompressedExt(d.Name()), trimCompressedExt(rel)) {
+			files = append(files, path)
+		}
+		return nil
+	})
+	sort.Strings(files)
+	return files, err
+}
+
+// readFileList lê uma captura por linha; linhas vazias e com # são ignoradas.
+func readFileList(r io.Reader) ([]string, error) {
+	var files []string
+	scanner := bufio.NewScanner(r)
+	for scanner.Scan() {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" || strings.HasPrefix(line, "#") {
+			continue
+		}
+		files = append(files, line)
+	}
+	return files, scanner.Err()
+}
+
+// loadFileList lê a lista de -list; "-" é a entrada padrão. Caminhos
+// relativos são relativos ao arquivo da lista.
+func loadFileList(path string) ([]string, error) {
+	if path == "-" {
+		return readFileList(os.Stdin)
+	}
+	f, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	defer f.Close()
+	files, err := readFileList(f)
+	for i, name := range files {
+		if !filepath.IsAbs(name) {
+			files[i] = filepath.Join(filepath.Dir(path), name)
+		}
+	}
+	return files, err
+}
+
+// splitList separa os padrões de -include e -exclude.
+func splitList(arg string) []string {
+	var items []string
+	for _, s := range strings.Split(arg, ",") {
+		if s = strings.TrimSpace(s); s != "" {
+			items = append(items, s)
+		}
+	}
+	return items
+}
+
+func trimCompressedExt(name string) string {
+	for _, ext := range compressedExts {
+		if strings.HasSuffix(name, ext) {
+			return strings.TrimSuffix(name, ext)
+		}
+	}
+	return name
+}
+
+// captureStem é o nome da captura sem a extensão de compressão e de captura:
+// radar.pcap.gz -> radar.
+func captureStem(filename string) string {
+	name := trimCompressedExt(filepath.Base(filename))
+	return strings.TrimSuffix(name, filepath.Ext(name))
+}
+
+// magic numbers dos formatos de compressão
+var (
+	gzipMagic = []byte{0x1f, 0x8b}
+	zstdMagic = []byte{0x28, 0xb5, 0x2f, 0xfd}
+	xzMagic   = []byte{0xfd, '7', 'z', 'X', 'Z', 0x00}
+)
+
+// inputFile é uma captura aberta, descomprimida quando necessário.
+type inputFile struct {
+	io.Reader
+	file    *os.File
+	raw     *digestReader // bytes do arquivo, antes da descompressão
+	modTime time.Time
+	close   func()
+}
+
+// digestReader calcula o hash e o tamanho dos bytes lidos.
+type digestReader struct {
+	r    io.Reader
+	h    hash.Hash
+	size int64
+}
+
+func (d *digestReader) Read(p []byte) (int, error) {
+	n, err := d.r.Read(p)
+	d.h.Write(p[:n])
+	d.size += int64(n)
+	return n, err
+}
+
+// openInput abre a captura e detecta gzip, zstd e xz pelo magic number,
+// não pela extensão.
+func openInput(path string) (*inputFile, error) {
+	f, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	st, err := f.Stat()
+	if err != nil {
+		f.Close()
+		return nil, err
+	}
+	raw := &digestReader{r: f, h: sha256.New()}
+	br := bufio.NewReaderSize(raw, 1<<20)
+	magic, _ := br.Peek(6)
+
+	in := &inputFile{Reader: br, file: f, raw: raw, modTime: st.ModTime()}
+	switch {
+	case bytes.HasPrefix(magic, gzipMagic):
+		zr, err := gzip.NewReader(br)
+		if err != nil {
+			f.Close()
+			return nil, fmt.Errorf("gzip: %w", err)
+		}
+		in.Reader, in.close = zr, func() { zr.Close() }
+	case bytes.HasPrefix(magic, zstdMagic):
+		zr, err := zstd.NewReader(br)
+		if err != nil {
+			f.Close()
+			return nil, fmt.Errorf("zstd: %w", err)
+		}
+		in.Reader, in.close = zr, zr.Close
+	case bytes.HasPrefix(magic, xzMagic):
+		xr, err := xz.NewReader(br)
+		if err != nil {
+			f.Close()
+			return nil, fmt.Errorf("xz: %w", err)
+		}
+		in.Reader = xr
+	}
+	return in, nil
+}
+
+// source descreve o conteúdo efetivamente lido, para o manifesto. O resto
+// do arquivo (bytes depois do fim do stream comprimido) também entra no hash.
+func (in *inputFile) source() (sourceState, error) {
+	if _, err := io.Copy(io.Discard, in.raw); err != nil {
+		return sourceState{}, err
+	}
+	return sourceState{
+		Size:    in.raw.size,
+		ModTime: in.modTime,
+		Hash:    hex.EncodeToString(in.raw.h.Sum(nil)),
+	}, nil
+}
+
+func (in *inputFile) Close() error {
+	if in.close != nil {
+		in.close()
+	}
+	return in.file.Close()
+}
@@ -1,29 +1,25 @@
 package main
 
 import (
-	"bufio"
 	"context"
-	"encoding/csv"
+	"errors"
 	"flag"
 	"fmt"
+	"io"
+	"math"
 	"os"
-	"os/exec"
+	"os/signal"
 	"path/filepath"
 	"runtime"
+	"slices"
+	"sort"
 	"strconv"
 	"strings"
 	"sync"
+	"syscall"
 	"time"
 
 	"github.com/pelletier/go-toml/v2"
-
-	"github.com/apache/arrow/go/v14/arrow"
-	"github.com/apache/arrow/go/v14/arrow/array"
-	"github.com/apache/arrow/go/v14/arrow/memory"
-	"github.com/apache/arrow/go/v14/parquet"
-	"github.com/apache/arrow/go/v14/parquet/compress"
-	"github.com/apache/arrow/go/v14/parquet/file"
-	"github.com/apache/arrow/go/v14/parquet/pqarrow"
 )
 
 //
@@ -31,188 +27,280 @@ import (
 //
 
 type DataItem struct {
-	Label string `toml:"label"`
-	Field string `toml:"field"`
-	Type  string `toml:"type"` // string, int32, int64, float32, float64, uint8, uint16
+	Label   string  `toml:"label"`
+	Field   string  `toml:"field"`
+	Type    string  `toml:"type"`  // string, dict, bool, int8..int64, uint8..uint64, float32, float64, timestamp, list<T>
+	Unit    string  `toml:"unit"`  // gravada nos metadados do campo Arrow
+	Scale   Factor  `toml:"scale"` // valor = bruto*scale + offset, ex. 0.25 ou "1/128" (ver nativeScale)
+	Offset  float64 `toml:"offset"`
+	OnError string  `toml:"on_error"` // null (padrão), keep_raw ou fail
+	TOD     string  `toml:"tod"`      // absolute ou latency, a partir da hora do dia ASTERIX
+	Expr    string  `toml:"expr"`     // coluna derivada de outros labels, ex. "hypot(VX, VY)"
 }
 
-type TShark struct {
-	Path       string   `toml:"path"`
-	Parameters []string `toml:"parameters"`
+// Factor aceita número ou texto com fração/potência: 0.25, "1/128", "180/2^23".
+type Factor float64
+
+func (f *Factor) UnmarshalText(b []byte) error {
+	s := strings.ReplaceAll(string(b), " ", "")
+	num, den, hasDen := strings.Cut(s, "/")
+	n, err := parseFactorTerm(num)
+	if err != nil {
+		return fmt.Errorf("scale inválido %q", s)
+	}
+	if hasDen {
+		d, err := parseFactorTerm(den)
+		if err != nil || d == 0 {
+			return fmt.Errorf("scale inválido %q", s)
+		}
+		n /= d
+	}
+	*f = Factor(n)
+	return nil
 }
 
-type Config struct {
-	Tshark    TShark                `toml:"tshark"`
-	Datagroup map[string][]DataItem `toml:"datagroup"`
-	Frame     []DataItem            `toml:"frame"` // campos a colocar no início
+func parseFactorTerm(s string) (float64, error) {
+	if base, exp, ok := strings.Cut(s, "^"); ok {
+		b, err := strconv.ParseFloat(base, 64)
+		if err != nil {
+			return 0, err
+		}
+		e, err := strconv.ParseFloat(exp, 64)
+		if err != nil {
+			return 0, err
+		}
+		return math.Pow(b, e), nil
+	}
+	return strconv.ParseFloat(s, 64)
 }
 
-func loadConfig(path string) (Config, error) {
-	var cfg Config
-	data, err := os.ReadFile(path)
-	if err != nil {
-		return cfg, err
+// scaleValue converte o valor bruto em unidade de engenharia. Valores
+// repetidos ("a,b") são convertidos um a um; valores não numéricos ficam
+// como estão.
+func (f DataItem) scaleValue(v string) string {
+	if v == "" || (f.Scale == 0 && f.Offset == 0) {
+		return v
+	}
+	scale := float64(f.Scale)
+	if scale == 0 {
+		scale = 1
+	}
+	parts := strings.Split(v, ",")
+	for i, p := range parts {
+		x, err := parseNumber(p)
+		if err != nil {
+			return v
+		}
+		parts[i] = strconv.FormatFloat(x*scale+f.Offset, 'f', -1, 64)
 	}
-	err = toml.Unmarshal(data, &cfg)
-	return cfg, err
+	return strings.Join(parts, ",")
 }
 
-//
-// ---------------- ARROW / PARQUET ----------------
-//
+// parseNumber aceita decimal, hexadecimal (0x3C) e ponto flutuante, com
+// as regras de parseInt: zeros à esquerda não indicam octal.
+func parseNumber(s string) (float64, error) {
+	if i, err := parseIntDigits(s, 64); err == nil {
+		return float64(i), nil
+	}
+	return strconv.ParseFloat(s, 64)
+}
 
-type ParquetWriter struct {
-	schema   *arrow.Schema
-	writer   *pqarrow.FileWriter
-	builders []array.Builder
-	mem      memory.Allocator
-	rows     int
-	batch    int
+type TShark struct {
+	Path       string   `toml:"path"`
+	Parameters []string `toml:"parameters"`
 }
 
-func (f DataItem) ArrowType() arrow.DataType {
-	switch f.Type {
-	case "int32":
-		return arrow.PrimitiveTypes.Int32
-	case "int64":
-		return arrow.PrimitiveTypes.Int64
-	case "float32":
-		return arrow.PrimitiveTypes.Float32
-	case "float64":
-		return arrow.PrimitiveTypes.Float64
-	case "uint8":
-		return arrow.PrimitiveTypes.Uint8
-	case "uint16":
-		return arrow.PrimitiveTypes.Uint16
-	default:
-		return arrow.BinaryTypes.String
-	}
+type DecoderConfig struct {
+	Backend  string            `toml:"backend"`  // native (padrão) ou tshark
+	Specs    string            `toml:"specs"`    // diretório com especificações XML/JSON
+	Editions map[string]string `toml:"editions"` // edição por categoria, ex. "021" = "2.4"
+	Timeout  string            `toml:"timeout"`  // tempo máximo por captura, ex. "30m" (padrão sem limite)
 }
 
-func newParquetWriter(path string, fields []DataItem) (*ParquetWriter, error) {
-	mem := memory.NewGoAllocator()
+// Datagroup aceita tanto a lista [[datagroup.x]] quanto a tabela
+// [datagroup.x] com opções e [[datagroup.x.fields]].
+type Datagroup struct {
+	Fields   []DataItem        `toml:"fields"`
+	Editions map[string]string `toml:"editions"` // sobrepõe decoder.editions
 
-	arrowFields := make([]arrow.Field, len(fields))
-	builders := make([]array.Builder, len(fields))
+	// filtros aplicados antes de gerar as linhas
+	Categories []int    `toml:"categories"` // ex. [21]
+	SacSic     []string `toml:"sac_sic"`    // "SAC/SIC", ex. ["20/129"]
+	SrcIP      []string `toml:"src_ip"`     // ip, ip/prefixo ou ip:porta
+	SrcPort    []int    `toml:"src_port"`
+	Multicast  []string `toml:"multicast"` // grupo de destino, ip ou ip:porta
 
-	for i, f := range fields {
-		arrowFields[i] = arrow.Field{
-			Name:     f.Label,
-			Type:     f.ArrowType(),
-			Nullable: true,
-		}
-		builders[i] = array.NewBuilder(mem, f.ArrowType())
-	}
+	// filtro aplicado à linha convertida, ex. "NACP >= 8 && CALLSIGN != ''"
+	Where string `toml:"where"`
 
-	schema := arrow.NewSchema(arrowFields, nil)
+	Output OutputConfig `toml:"output"` // sobrepõe [output]
+}
 
-	file, err := os.Create(path)
-	if err != nil {
-		return nil, err
-	}
+// OutputConfig escolhe os formatos de saída e as opções de cada um.
+type OutputConfig struct {
+	Formats []string       `toml:"formats"` // parquet (padrão), csv, ndjson, arrow, sqlite, dataset
+	Parquet ParquetOptions `toml:"parquet"` // também usado pelo dataset
+	CSV     CSVOptions     `toml:"csv"`
+	NDJSON  NDJSONOptions  `toml:"ndjson"`
+	Arrow   ArrowOptions   `toml:"arrow"`
+	SQLite  SQLiteOptions  `toml:"sqlite"`
+	Dataset DatasetOptions `toml:"dataset"`
+
+	Dir    string `toml:"dir"`    // diretório das saídas (padrão o atual); -out sobrepõe
+	Name   string `toml:"name"`   // modelo do nome, sem extensão (padrão "{stem}.{datagroup}")
+	Exists string `toml:"exists"` // saída que já existe: overwrite (padrão), skip ou fail
+}
 
-	props := parquet.NewWriterProperties(
-		parquet.WithCompression(compress.Codecs.Snappy),
-	)
+type Config struct {
+	Tshark    TShark               `toml:"tshark"`
+	Decoder   DecoderConfig        `toml:"decoder"`
+	Datagroup map[string]Datagroup `toml:"-"`
+	Frame     []DataItem           `toml:"frame"` // campos a colocar no início
+	Output    OutputConfig         `toml:"output"`
+	Input     InputOptions         `toml:"input"`
+}
 
-	writer, err := pqarrow.NewFileWriter(
-		schema,
-		file,
-		props,
-		pqarrow.DefaultWriterProps(),
-	)
+func loadConfig(path string) (Config, error) {
+	cfg, err := readConfig(path)
 	if err != nil {
-		return nil, err
+		return cfg, err
 	}
+	if errs := cfg.validate(); len(errs) > 0 {
+		return cfg, errs[0]
+	}
+	return cfg, nil
+}
 
-	return &ParquetWriter{
-		schema:   schema,
-		writer:   writer,
-		builders: builders,
-		mem:      mem,
-		batch:    1024,
-	}, nil
+// readConfig lê a configuração sem validá-la; pshark check usa a lista
+// completa de erros de validate.
+func readConfig(path string) (Config, error) {
+	var cfg Config
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return cfg, err
+	}
+	if err = toml.Unmarshal(data, &cfg); err != nil {
+		return cfg, err
+	}
+	if cfg.Datagroup, err = decodeDatagroups(data); err != nil {
+		return cfg, err
+	}
+	if cfg.Decoder.Specs != "" && !filepath.IsAbs(cfg.Decoder.Specs) {
+		cfg.Decoder.Specs = filepath.Join(filepath.Dir(path), cfg.Decoder.Specs)
+	}
+	if cfg.Decoder.Backend == "" {
+		cfg.Decoder.Backend = "native"
+	}
+	return cfg, nil
 }
 
-func appendValue(b array.Builder, v string) {
-	if v == "" {
-		b.AppendNull()
-		return
-	}
-
-	switch bb := b.(type) {
-	case *array.Int32Builder:
-		var x int32
-		fmt.Sscan(v, &x)
-		bb.Append(x)
-	case *array.Int64Builder:
-		var x int64
-		fmt.Sscan(v, &x)
-		bb.Append(x)
-	case *array.Float32Builder:
-		var x float32
-		fmt.Sscan(v, &x)
-		bb.Append(x)
-	case *array.Float64Builder:
-		var x float64
-		fmt.Sscan(v, &x)
-		bb.Append(x)
-	case *array.StringBuilder:
-		bb.Append(v)
-	case *array.Uint8Builder:
-		var x uint8
-		fmt.Sscan(v, &x)
-		bb.Append(x)
-	case *array.Uint16Builder:
-		var x uint16
-		fmt.Sscan(v, &x)
-		bb.Append(x)
+// validate retorna todos os erros da configuração, com os datagroups em
+// ordem alfabética.
+func (cfg Config) validate() []error {
+	var errs []error
+	for _, f := range cfg.Frame {
+		if err := f.validate(); err != nil {
+			errs = append(errs, fmt.Errorf("frame: %w", err))
+		}
+	}
+	if err := cfg.Output.validate(); err != nil {
+		errs = append(errs, fmt.Errorf("output: %w", err))
+	}
+	if err := cfg.Input.validate(); err != nil {
+		errs = append(errs, fmt.Errorf("input: %w", err))
+	}
+	names := make([]string, 0, len(cfg.Datagroup))
+	for name := range cfg.Datagroup {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	for _, name := range names {
+		dg := cfg.Datagroup[name]
+		for _, f := range dg.Fields {
+			if err := f.validate(); err != nil {
+				errs = append(errs, fmt.Errorf("datagroup.%s: %w", name, err))
+			}
+		}
+		if err := cfg.Output.merge(dg.Output).validate(); err != nil {
+			errs = append(errs, fmt.Errorf("datagroup.%s.output: %w", name, err))
+		}
+	}
+	switch cfg.Decoder.Backend {
+	case "native", "tshark":
 	default:
-		b.AppendNull()
+		errs = append(errs, fmt.Errorf("decoder.backend inválido: %q", cfg.Decoder.Backend))
+	}
+	if cfg.Decoder.Timeout != "" {
+		if d, err := time.ParseDuration(cfg.Decoder.Timeout); err != nil || d < 0 {
+			errs = append(errs, fmt.Errorf("decoder.timeout inválido: %q", cfg.Decoder.Timeout))
+		}
 	}
+	return errs
 }
 
-func (p *ParquetWriter) WriteRow(values []string) error {
-	for i, v := range values {
-		appendValue(p.builders[i], v)
+func (f DataItem) validate() error {
+	if !validType(f.Type) {
+		return fmt.Errorf("%s: type desconhecido %q", f.Label, f.Type)
+	}
+	if !validOnError(f.OnError) {
+		return fmt.Errorf("%s: on_error inválido %q", f.Label, f.OnError)
+	}
+	if !validTOD(f.TOD) {
+		return fmt.Errorf("%s: tod inválido %q", f.Label, f.TOD)
+	}
+	if f.Expr != "" && f.Field != "" {
+		return fmt.Errorf("%s: use field ou expr, não os dois", f.Label)
+	}
+	// escala fracionária numa coluna inteira falharia em todas as linhas
+	t := f.Type
+	if elem, ok := listElem(t); ok {
+		t = elem
 	}
-	p.rows++
-	if p.rows >= p.batch {
-		return p.flush()
+	if strings.HasPrefix(t, "int") || strings.HasPrefix(t, "uint") {
+		if s := float64(f.Scale); s != math.Trunc(s) || f.Offset != math.Trunc(f.Offset) {
+			return fmt.Errorf("%s: scale/offset fracionário num type %s; use float32 ou float64", f.Label, f.Type)
+		}
 	}
 	return nil
 }
 
-func (p *ParquetWriter) flush() error {
-	arrays := make([]arrow.Array, len(p.builders))
-	for i, b := range p.builders {
-		arrays[i] = b.NewArray()
+func decodeDatagroups(data []byte) (map[string]Datagroup, error) {
+	var raw struct {
+		Datagroup map[string]any `toml:"datagroup"`
 	}
-
-	record := array.NewRecord(p.schema, arrays, int64(p.rows))
-	if err := p.writer.Write(record); err != nil {
-		return err
+	if err := toml.Unmarshal(data, &raw); err != nil {
+		return nil, err
 	}
 
-	record.Release()
-
-	// recria os builders
-	for i, f := range p.schema.Fields() {
-		p.builders[i].Release()
-		p.builders[i] = array.NewBuilder(p.mem, f.Type)
+	groups := map[string]Datagroup{}
+	for name, v := range raw.Datagroup {
+		// formato antigo: [[datagroup.x]] é só a lista de campos
+		if list, ok := v.([]any); ok {
+			v = map[string]any{"fields": list}
+		}
+		b, err := toml.Marshal(v)
+		if err != nil {
+			return nil, fmt.Errorf("datagroup.%s: %w", name, err)
+		}
+		var dg Datagroup
+		if err := toml.Unmarshal(b, &dg); err != nil {
+			return nil, fmt.Errorf("datagroup.%s: %w", name, err)
+		}
+		groups[name] = dg
 	}
-
-	p.rows = 0
-	return nil
+	return groups, nil
 }
 
-func (p *ParquetWriter) Close() error {
-	if p.rows > 0 {
-		if err := p.flush(); err != nil {
-			return err
-		}
+// editionsFor combina as edições globais com as do datagroup.
+func (c Config) editionsFor(name string) map[string]string {
+	eds := map[string]string{}
+	for k, v := range c.Decoder.Editions {
+		eds[k] = v
 	}
-	return p.writer.Close()
+	for k, v := range c.Datagroup[name].Editions {
+		eds[k] = v
+	}
+	return eds
 }
 
 //
@@ -221,266 +309,388 @@ func (p *ParquetWriter) Close() error {
 
 type Job struct {
 	File string
+	Dir  string // {dir} do modelo de nome
 }
 
 type App struct {
-	cfg       Config
-	datalist  string
-	timestamp bool
-	genCSV    bool
-	jobs      chan Job
-	wg        sync.WaitGroup
+	cfg        Config
+	groups     []*Group
+	timestamp  bool
+	jobs       chan Job
+	wg         sync.WaitGroup
+	manifest   *Manifest
+	configs    map[string]string // configHashes, por datagroup
+	timeout    time.Duration     // por captura; 0 = sem limite
+
+	mu      sync.Mutex
+	results []fileResult
 }
 
-func (a *App) worker() {
-	for job := range a.jobs {
-		a.processFile(job.File)
-		a.wg.Done()
-	}
+// Group é um datagroup pronto para uso: campos, decoder e filtros.
+type Group struct {
+	Name    string
+	Fields  []DataItem // campos de frame primeiro, depois os do datagroup
+	Exprs   []*Expr    // expressão de cada campo, nil para campos lidos da captura
+	Where   *Expr      // nil = todas as linhas
+	Output  OutputConfig
+	Decoder *AsterixDecoder
+	Filter  *RecordFilter
 }
 
-func (a *App) processFile(filename string) {
-	start := time.Now()
+// newGroups prepara os datagroups pedidos. Datagroups com as mesmas
+// edições compartilham o decoder, para decodificar cada pacote uma vez.
+func newGroups(cfg Config, specs *SpecSet, names []string) ([]*Group, error) {
+	decoders := map[string]*AsterixDecoder{}
+	var groups []*Group
 
-	// 🔹 campos de frame primeiro, depois categoria
-	fields := append(a.cfg.Frame, a.cfg.Datagroup[a.datalist]...)
-	if len(fields) == 0 {
-		fmt.Printf("❌ CAT %s não encontrada\n", a.datalist)
-		return
-	}
+	for _, name := range names {
+		dg, ok := cfg.Datagroup[name]
+		if !ok {
+			return nil, fmt.Errorf("datagroup %s não encontrado", name)
+		}
+
+		eds := cfg.editionsFor(name)
+		keys := make([]string, 0, len(eds))
+		for k, v := range eds {
+			keys = append(keys, k+"="+v)
+		}
+		sort.Strings(keys)
+		key := strings.Join(keys, ",")
+
+		dec, ok := decoders[key]
+		if !ok {
+			var err error
+			if dec, err = specs.Resolve(eds); err != nil {
+				return nil, fmt.Errorf("datagroup %s: %w", name, err)
+			}
+			decoders[key] = dec
+		}
 
-	outfile := strings.TrimSuffix(filepath.Base(filename), filepath.Ext(filename)) + ".parquet"
+		filter, err := compileFilter(dg)
+		if err != nil {
+			return nil, fmt.Errorf("datagroup %s: %w", name, err)
+		}
 
-	args := []string{"-r", filename}
-	args = append(args, a.cfg.Tshark.Parameters...)
-	//args = append(args, "-Y", "asterix.category=="+a.category)
+		fields := append(append([]DataItem{}, cfg.Frame...), dg.Fields...)
+		if cfg.Decoder.Backend != "tshark" {
+			nativeScale(fields, dec)
+		}
+		exprs, err := compileExprs(fields)
+		if err != nil {
+			return nil, fmt.Errorf("datagroup %s: %w", name, err)
+		}
+		where, err := compileWhere(dg.Where, fields)
+		if err != nil {
+			return nil, fmt.Errorf("datagroup %s: where: %w", name, err)
+		}
+		groups = append(groups, &Group{
+			Name:    name,
+			Fields:  fields,
+			Exprs:   exprs,
+			Where:   where,
+			Output:  cfg.Output.merge(dg.Output),
+			Decoder: dec,
+			Filter:  filter,
+		})
+	}
+	return groups, nil
+}
 
-	// adiciona campos de frame primeiro
-	for _, f := range a.cfg.Frame {
-		args = append(args, "-e", f.Field)
+// nativeScale descarta scale e offset dos campos que o decoder nativo já
+// entrega em unidade de engenharia (elemento com LSB na UAP). No tshark eles
+// valem sobre o valor bruto, e a mesma configuração dá os mesmos números
+// nos dois backends.
+func nativeScale(fields []DataItem, dec *AsterixDecoder) {
+	elems := dec.fieldElements()
+	for i, f := range fields {
+		if e, ok := elems[f.Field]; ok && e.LSB != 0 {
+			fields[i].Scale, fields[i].Offset = 0, 0
+		}
 	}
+}
 
-	// depois os campos da categoria
-	for _, f := range a.cfg.Datagroup[a.datalist] {
-		args = append(args, "-e", f.Field)
+// compileExprs compila as colunas derivadas. Uma expressão só pode usar
+// campos lidos da captura ou colunas derivadas definidas antes dela.
+func compileExprs(fields []DataItem) ([]*Expr, error) {
+	exprs := make([]*Expr, len(fields))
+	known := map[string]bool{}
+	for _, f := range fields {
+		if f.Expr == "" {
+			known[f.Label] = true
+		}
 	}
-
-	cmd := exec.Command(a.cfg.Tshark.Path, args...)
-	stdout, _ := cmd.StdoutPipe()
-	stderr, _ := cmd.StderrPipe()
-
-	go func() {
-		scanner := bufio.NewScanner(stderr)
-		for scanner.Scan() {
-			fmt.Println("[tshark]", scanner.Text())
+	for i, f := range fields {
+		if f.Expr == "" {
+			continue
 		}
-	}()
-
-	if err := cmd.Start(); err != nil {
-		fmt.Println("❌ falha ao iniciar tshark:", err)
-		return
+		e, err := compileExpr(f.Expr)
+		if err != nil {
+			return nil, fmt.Errorf("%s: %w", f.Label, err)
+		}
+		for _, ref := range e.Refs() {
+			if !known[ref] {
+				return nil, fmt.Errorf("%s: label desconhecido ou definido depois: %s", f.Label, ref)
+			}
+		}
+		exprs[i] = e
+		known[f.Label] = true
 	}
+	return exprs, nil
+}
 
-	pw, err := newParquetWriter(outfile, fields)
+// compileWhere compila o filtro de linhas, que pode usar qualquer label.
+func compileWhere(src string, fields []DataItem) (*Expr, error) {
+	if src == "" {
+		return nil, nil
+	}
+	e, err := compileExpr(src)
 	if err != nil {
-		fmt.Println("❌ parquet:", err)
-		return
+		return nil, err
 	}
+	known := map[string]bool{}
+	for _, f := range fields {
+		known[f.Label] = true
+	}
+	for _, ref := range e.Refs() {
+		if !known[ref] {
+			return nil, fmt.Errorf("label desconhecido: %s", ref)
+		}
+	}
+	return e, nil
+}
 
-	scanner := bufio.NewScanner(stdout)
-	scanner.Buffer(make([]byte, 64*1024), 10*1024*1024)
-
-	lineCount := 0
-
-	for scanner.Scan() {
-		raw := strings.Split(scanner.Text(), ";")
-
-		// separa cada campo que pode ter múltiplos valores
-		split := make([][]string, len(raw))
-		max := 1
-		for i, f := range raw {
-			parts := strings.Split(f, ",")
-			for j := range parts {
-				parts[j] = strings.TrimSpace(parts[j])
-			}
-			split[i] = parts
-			if len(parts) > max {
-				max = len(parts)
-			}
+// datagroupNames interpreta -g: lista separada por vírgulas ou "all".
+func datagroupNames(cfg Config, arg string) []string {
+	var names []string
+	if arg == "all" {
+		for name := range cfg.Datagroup {
+			names = append(names, name)
 		}
+		sort.Strings(names)
+		return names
+	}
+	for _, name := range strings.Split(arg, ",") {
+		if name = strings.TrimSpace(name); name != "" {
+			names = append(names, name)
+		}
+	}
+	return names
+}
 
-		// 🔹 salva os valores de frame (timestamp, etc)
-		frameValues := make([]string, len(a.cfg.Frame))
-		for i := range a.cfg.Frame {
-			if len(split) > i && split[i][0] != "" {
-				frameValues[i] = split[i][0]
+func (a *App) worker(ctx context.Context) {
+	for job := range a.jobs {
+		res := a.processFile(ctx, job)
+		if res.status == StatusOK {
+			if err := a.manifest.record(job.File, a.configs, res.source, res.outputs, res.rows); err != nil {
+				fmt.Println("⚠ manifesto:", err)
 			}
 		}
+		a.mu.Lock()
+		a.results = append(a.results, res)
+		a.mu.Unlock()
+		a.wg.Done()
+	}
+}
 
-		// para cada “linha” da mensagem ASTERIX
-		for i := 0; i < max; i++ {
-			row := make([]string, len(fields))
-			// frame primeiro
-			copy(row[:len(frameValues)], frameValues)
-
-			// categoria ASTERIX
-			offset := len(frameValues)
-			for j := 0; j < len(a.cfg.Datagroup[a.datalist]); j++ {
-				idx := offset + j
-				if idx < len(split) && i < len(split[idx]) {
-					row[offset+j] = split[idx][i]
-				} else {
-					row[offset+j] = ""
-				}
-			}
+// processFile decodifica o arquivo uma única vez e distribui os registros
+// para os sinks de cada datagroup.
+func (a *App) processFile(ctx context.Context, job Job) (res fileResult) {
+	start := time.Now()
+	filename := job.File
+	res = fileResult{file: filename, status: StatusOK, outputs: map[string][]string{}, rows: map[string]int{}}
+	defer func() { res.duration = time.Since(start) }()
 
-			_ = pw.WriteRow(row)
+	if a.timeout > 0 {
+		var cancel context.CancelFunc
+		ctx, cancel = context.WithTimeout(ctx, a.timeout)
+		defer cancel()
+	}
 
-		}
+	stem := captureStem(filename)
 
-		lineCount++
-		if lineCount%5000 == 0 {
-			fmt.Printf("\r%s: %d pacotes processados.   ", filepath.Base(filename), lineCount)
+	outputs := make([]*groupOutput, 0, len(a.groups))
+	abortAll := func() {
+		for _, o := range outputs {
+			o.Abort()
 		}
 	}
-	fmt.Println()
-
-	if err := cmd.Wait(); err != nil {
-		fmt.Println("❌ erro tshark:", err)
+	for _, g := range a.groups {
+		o, err := openOutputs(g, job, stem, newConverter(g.Fields, g.Exprs, g.Where))
+		if err != nil {
+			res.fail(&FileError{File: filename, Group: g.Name, Stage: StageOpen, Err: err})
+			abortAll()
+			return res
+		}
+		for _, path := range o.skipped {
+			fmt.Printf("%s [%s]: %s já existe, ignorado\n", filepath.Base(filename), g.Name, path)
+		}
+		outputs = append(outputs, o)
 	}
-
-	if err := pw.Close(); err != nil {
-		fmt.Println("❌ parquet close:", err)
-		return
+	if !slices.ContainsFunc(outputs, func(o *groupOutput) bool { return len(o.sinks) > 0 }) {
+		fmt.Printf("%s: saídas já existem, arquivo ignorado\n", filepath.Base(filename))
+		res.status = StatusSkipped
+		return res
 	}
 
-	if a.genCSV {
-		if err := parquetToCSV(outfile, a.cfg); err != nil {
-			fmt.Println("❌ CSV:", err)
+	// cada registro ASTERIX vira uma linha, com os campos de quadro repetidos
+	packets := 0
+	badPackets := 0
+	emit := func(p Packet) error {
+		packets++
+		if packets%5000 == 0 {
+			fmt.Printf("\r%s: %d pacotes processados.   ", filepath.Base(filename), packets)
 		}
-	}
 
-	fmt.Printf("✔ %s → %s (%.2fs)\n", filepath.Base(filename), outfile, time.Since(start).Seconds())
-}
+		decoded := map[*AsterixDecoder][]Record{}
+		bad := false
+		for _, o := range outputs {
+			g := o.group
+			if !g.Filter.MatchPacket(p) {
+				continue
+			}
 
-func getCSVFieldOrder(cfg Config, schema *arrow.Schema) []int {
-	order := []int{}
-	frameFields := map[string]bool{}
-	for _, f := range cfg.Frame {
-		frameFields[f.Label] = true
-	}
+			records := p.Records
+			if p.Payload != nil {
+				var ok bool
+				if records, ok = decoded[g.Decoder]; !ok {
+					// registros decodificados antes de um bloco inválido são mantidos
+					var err error
+					if records, err = g.Decoder.Decode(p.Payload); err != nil {
+						bad = true
+					}
+					decoded[g.Decoder] = records
+				}
+			}
 
-	// Primeiro índices dos campos frame
-	for i, f := range schema.Fields() {
-		if frameFields[f.Name] {
-			order = append(order, i)
+			for _, rec := range records {
+				if !g.Filter.MatchRecord(rec) {
+					continue
+				}
+				row, keep, err := o.conv.Convert(buildRow(g.Fields, p, rec))
+				if err != nil {
+					return &FileError{File: filename, Group: g.Name, Stage: StageConvert, Err: err}
+				}
+				if !keep {
+					continue
+				}
+				if err := o.WriteRow(row); err != nil {
+					return &FileError{File: filename, Group: g.Name, Stage: StageWrite, Err: err}
+				}
+			}
 		}
-	}
-
-	// Depois os outros campos
-	for i, f := range schema.Fields() {
-		if !frameFields[f.Name] {
-			order = append(order, i)
+		if bad {
+			badPackets++
 		}
+		return nil
 	}
-	return order
-}
-
-func parquetToCSV(parquetFile string, cfg Config) error {
-	csvFile := strings.TrimSuffix(parquetFile, ".parquet") + ".csv"
 
-	f, err := os.Open(parquetFile)
-	if err != nil {
-		return err
+	in, err := openInput(filename)
+	if err == nil {
+		if a.cfg.Decoder.Backend == "tshark" {
+			err = a.readTshark(ctx, in, emit)
+		} else {
+			err = a.readNative(ctx, in, emit)
+		}
+		if err == nil {
+			res.source, err = in.source()
+		}
+		in.Close()
 	}
-	defer f.Close()
-
-	pqReader, err := file.NewParquetReader(f)
-	if err != nil {
-		return err
+	fmt.Println()
+	switch {
+	case errors.Is(err, context.DeadlineExceeded):
+		err = fmt.Errorf("tempo limite de %s excedido", a.timeout)
+	case errors.Is(err, context.Canceled):
+		err = errors.New("interrompido")
 	}
-	defer pqReader.Close()
-
-	mem := memory.NewGoAllocator()
-	arrowReader, err := pqarrow.NewFileReader(pqReader, pqarrow.ArrowReadProperties{}, mem)
 	if err != nil {
-		return err
+		var fe *FileError
+		if !errors.As(err, &fe) {
+			err = &FileError{File: filename, Stage: StageRead, Err: err}
+		}
+		res.fail(err)
 	}
-
-	table, err := arrowReader.ReadTable(context.Background())
-	if err != nil {
-		return err
+	if badPackets > 0 {
+		fmt.Printf("⚠ %s: %d pacotes com ASTERIX inválido\n", filepath.Base(filename), badPackets)
 	}
-	defer table.Release()
 
-	out, err := os.Create(csvFile)
-	if err != nil {
-		return err
-	}
-	defer out.Close()
+	failed := res.status == StatusFailed
+	for _, o := range outputs {
+		if summary := o.conv.Summary(); summary != "" {
+			fmt.Printf("⚠ %s [%s] falhas de conversão: %s\n", filepath.Base(filename), o.group.Name, summary)
+		}
+		if n := o.conv.Dropped(); n > 0 {
+			fmt.Printf("%s [%s]: %d linhas descartadas pelo where\n", filepath.Base(filename), o.group.Name, n)
+		}
 
-	w := csv.NewWriter(out)
-	w.Comma = ';'
+		// captura incompleta: nenhuma saída fica com o nome final
+		if failed {
+			o.Abort()
+			continue
+		}
+		if err := o.Close(); err != nil {
+			res.fail(&FileError{File: filename, Group: o.group.Name, Stage: StageClose, Err: err})
+			continue
+		}
 
-	schema := table.Schema()
-	order := getCSVFieldOrder(cfg, schema)
+		files := o.files()
+		res.outputs[o.group.Name] = files
+		res.rows[o.group.Name] = o.rows
+		fmt.Printf("✔ %s → %s (%.2fs)\n", filepath.Base(filename), strings.Join(files, ", "), time.Since(start).Seconds())
+	}
+	return res
+}
 
-	// header
-	headers := make([]string, len(schema.Fields()))
-	for i, idx := range order {
-		headers[i] = schema.Field(idx).Name
+// buildRow monta a linha de um registro na ordem dos campos configurados.
+// Itens ausentes no registro ficam vazios (null), sem herdar valores de
+// outros registros do mesmo pacote.
+func buildRow(fields []DataItem, p Packet, rec Record) []string {
+	row := make([]string, len(fields))
+	for i, f := range fields {
+		if v, ok := rec.Fields[f.Field]; ok {
+			row[i] = v
+		} else {
+			row[i] = p.Fields[f.Field]
+		}
+		if f.TOD != "" {
+			// o dia é escolhido com a hora do dia já em segundos
+			row[i] = resolveTOD(f.TOD, f.scaleValue(row[i]), p.Fields["frame.time_epoch"])
+		}
 	}
-	if err := w.Write(headers); err != nil {
+	return row
+}
+
+// readNative lê a captura sem depender do tshark. Os pacotes saem com o
+// payload UDP; a decodificação ASTERIX fica a cargo de cada datagroup.
+func (a *App) readNative(ctx context.Context, in *inputFile, emit func(Packet) error) error {
+	pr, err := OpenCapture(in)
+	if err != nil {
 		return err
 	}
+	demux := NewUDPDemux()
 
-	// records
-	recReader := array.NewTableReader(table, 1024)
-	defer recReader.Release()
-
-	for recReader.Next() {
-		rec := recReader.Record()
-		rows := int(rec.NumRows())
-		cols := int(rec.NumCols())
-
-		for r := 0; r < rows; r++ {
-			line := make([]string, cols)
-			for i, idx := range order {
-				line[i] = valueAt(rec.Column(idx), r)
-			}
-			if err := w.Write(line); err != nil {
-				return err
-			}
+	for {
+		if err := ctx.Err(); err != nil {
+			return err
+		}
+		fr, err := pr.Next()
+		if err == io.EOF {
+			return nil
+		}
+		if err != nil {
+			return err
 		}
-		rec.Release()
-	}
 
-	w.Flush()
-	return w.Error()
-}
+		dg, ok := demux.Parse(fr)
+		if !ok {
+			continue
+		}
 
-func valueAt(arr arrow.Array, row int) string {
-	if arr.IsNull(row) {
-		return ""
-	}
-
-	switch a := arr.(type) {
-	case *array.Int32:
-		return strconv.FormatInt(int64(a.Value(row)), 10)
-	case *array.Int64:
-		return strconv.FormatInt(a.Value(row), 10)
-	case *array.Float32:
-		return strconv.FormatFloat(float64(a.Value(row)), 'f', -1, 32)
-	case *array.Float64:
-		return strconv.FormatFloat(a.Value(row), 'f', -1, 64)
-	case *array.String:
-		return a.Value(row)
-	case *array.Uint8:
-		return strconv.FormatUint(uint64(a.Value(row)), 10)
-	case *array.Uint16:
-		return strconv.FormatUint(uint64(a.Value(row)), 10)
-	default:
-		return ""
+		p := Packet{Fields: dg.Fields(), Payload: dg.Payload}
+		p.Fields["frame.time_epoch"] = epochString(fr.Time)
+		if err := emit(p); err != nil {
+			return err
+		}
 	}
 }
 
@@ -489,55 +699,243 @@ func valueAt(arr arrow.Array, row int) string {
 //
 
 func main() {
+	if len(os.Args) > 1 {
+		switch os.Args[1] {
+		case "check":
+			os.Exit(runCheck(os.Args[2:]))
+		case "fields":
+			os.Exit(runFields(os.Args[2:]))
+		case "init":
+			os.Exit(runInit(os.Args[2:]))
+		}
+	}
+	os.Exit(run())
+}
+
+// run retorna o código de saída: exitConfig antes de ler qualquer captura,
+// exitFailed se alguma falhou.
+func run() int {
 	file := flag.String("f", "", "PCAP file")
 	dir := flag.String("d", "", "PCAP directory")
-	datagroup := flag.String("g", "", "PCAP datagroup")
+	recursive := flag.Bool("r", false, "Incluir os subdiretórios de -d")
+	include := flag.String("include", "", "Padrões de arquivo aceitos em -d, separados por vírgula (padrão *.pcap,*.pcapng,*.cap)")
+	exclude := flag.String("exclude", "", "Padrões de arquivo ou diretório ignorados em -d, separados por vírgula")
+	list := flag.String("list", "", "Arquivo com uma captura por linha; - lê da entrada padrão")
+	force := flag.Bool("force", false, "Reprocessar capturas já convertidas segundo o manifesto")
+	manifestPath := flag.String("manifest", "", "Manifesto da execução (padrão <diretório das saídas>/"+manifestName+")")
+	datagroup := flag.String("g", "", "Datagroups separados por vírgula, ou all")
 	cfgPath := flag.String("cfg", "config.toml", "Config file")
 	workers := flag.Int("j", runtime.NumCPU(), "Workers")
-	csvFile := flag.Bool("csv", false, "Gerar CSV a partir do Parquet")
+	csvFile := flag.Bool("csv", false, "Gerar CSV junto com o Parquet (o mesmo que incluir csv em -o)")
+	outFormats := flag.String("o", "", "Formatos de saída separados por vírgula: parquet, csv, ndjson, arrow, sqlite, dataset")
+	outDir := flag.String("out", "", "Diretório das saídas (sobrepõe output.dir)")
+	timeout := flag.Duration("timeout", 0, "Tempo máximo por captura, ex. 30m (sobrepõe decoder.timeout)")
 
 	flag.Parse()
 
 	if *datagroup == "" {
-		fmt.Println("❌ Use -g <datagroup>")
-		return
+		fmt.Println("❌ Use -g <datagroup>[,<datagroup>...] ou -g all")
+		return exitConfig
 	}
 
 	cfg, err := loadConfig(*cfgPath)
 	if err != nil {
 		fmt.Println("❌ config:", err)
-		return
+		return exitConfig
+	}
+	if cfg.Decoder.Backend == "tshark" {
+		cfg.Tshark.warnIgnored(func(format string, args ...any) {
+			fmt.Printf("⚠ "+format+"\n", args...)
+		})
+	}
+
+	specs, err := loadSpecs(cfg)
+	if err != nil {
+		fmt.Println("❌ specs:", err)
+		return exitConfig
+	}
+
+	groups, err := newGroups(cfg, specs, datagroupNames(cfg, *datagroup))
+	if err != nil {
+		fmt.Println("❌", err)
+		return exitConfig
+	}
+	if len(groups) == 0 {
+		fmt.Println("❌ Nenhum datagroup configurado")
+		return exitConfig
+	}
+
+	formats := parseFormats(*outFormats)
+	if err := validFormats(formats); err != nil {
+		fmt.Println("❌ -o:", err)
+		return exitConfig
+	}
+	applyOutputFlags(groups, formats, *csvFile, *outDir)
+
+	input := cfg.Input
+	input.Recursive = input.Recursive || *recursive
+	if *include != "" {
+		input.Include = splitList(*include)
+	}
+	if *exclude != "" {
+		input.Exclude = splitList(*exclude)
+	}
+	if err := input.validate(); err != nil {
+		fmt.Println("❌", err)
+		return exitConfig
 	}
 
-	files := []string{}
+	// -f e -list: {dir} relativo ao diretório comum das capturas
+	var named []string
 	if *file != "" {
-		files = append(files, *file)
-	} else if *dir != "" {
-		matches, _ := filepath.Glob(filepath.Join(*dir, "*.pcap"))
-		files = append(files, matches...)
+		named = append(named, *file)
+	}
+	if *list != "" {
+		listed, err := loadFileList(*list)
+		if err != nil {
+			fmt.Println("❌ -list:", err)
+			return exitConfig
+		}
+		named = append(named, listed...)
+	}
+	jobs := []Job{}
+	base := commonDir(named)
+	for _, f := range named {
+		jobs = append(jobs, Job{File: f, Dir: inputDir(base, f)})
+	}
+	if *dir != "" {
+		found, err := findInputs(*dir, input)
+		if err != nil {
+			fmt.Println("❌", err)
+			return exitConfig
+		}
+		for _, f := range found {
+			jobs = append(jobs, Job{File: f, Dir: inputDir(*dir, f)})
+		}
+	}
+
+	// a mesma captura em -f, -d e -list é processada uma vez só
+	unique := jobs[:0]
+	seenJobs := map[string]bool{}
+	for _, job := range jobs {
+		key, err := manifestKey(job.File)
+		if err != nil || !seenJobs[key] {
+			unique = append(unique, job)
+			seenJobs[key] = true
+		}
 	}
+	jobs = unique
 
-	if len(files) == 0 {
+	if len(jobs) == 0 {
 		fmt.Println("❌ Nenhum arquivo PCAP encontrado")
-		return
+		return exitConfig
+	}
+	if err := checkCollisions(jobs, groups); err != nil {
+		fmt.Println("❌", err)
+		return exitConfig
 	}
 
-	app := App{
-		cfg:      cfg,
-		datalist: *datagroup,
-		jobs:     make(chan Job),
-		genCSV:   *csvFile,
+	// manifesto: só as capturas novas, alteradas ou com outra configuração
+	if *manifestPath == "" {
+		root := cfg.Output.Dir
+		if *outDir != "" {
+			root = *outDir
+		}
+		*manifestPath = filepath.Join(root, manifestName)
+	}
+	manifest, err := loadManifest(*manifestPath)
+	if err != nil {
+		fmt.Println("❌ manifesto:", err)
+		return exitConfig
 	}
+	configs, err := configHashes(cfg, groups)
+	if err != nil {
+		fmt.Println("❌ manifesto:", err)
+		return exitConfig
+	}
+	upToDate := 0
+	if !*force {
+		pending := jobs[:0]
+		for _, job := range jobs {
+			if !manifest.upToDate(job.File, configs) {
+				pending = append(pending, job)
+			}
+		}
+		if upToDate = len(jobs) - len(pending); upToDate > 0 {
+			fmt.Printf("%d arquivos já convertidos com a mesma configuração, ignorados (use -force para reprocessar)\n", upToDate)
+		}
+		jobs = pending
+	}
+
+	app := App{
+		cfg:        cfg,
+		groups:     groups,
+		jobs:       make(chan Job),
+		manifest:   manifest,
+		configs:    configs,
+		timeout:    *timeout,
+	}
+	if app.timeout == 0 && cfg.Decoder.Timeout != "" {
+		app.timeout, _ = time.ParseDuration(cfg.Decoder.Timeout) // validado em loadConfig
+	}
+
+	// SIGINT/SIGTERM cancela ctx: o tshark é encerrado, as capturas em
+	// andamento são descartadas e as já concluídas, o banco da campanha e o
+	// dataset são finalizados. Um segundo sinal sai na hora.
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+	sig := make(chan os.Signal, 2)
+	signal.Notify(sig, os.Interrupt, syscall.SIGTERM)
+	go func() {
+		s := <-sig
+		fmt.Printf("\n⚠ %s: encerrando (repita para sair imediatamente)\n", s)
+		cancel()
+		<-sig
+		removePartials()
+		os.Exit(exitFailed)
+	}()
 
 	for i := 0; i < *workers; i++ {
-		go app.worker()
+		go app.worker(ctx)
 	}
 
-	for _, f := range files {
+	pending := len(jobs)
+feed:
+	for _, job := range jobs {
 		app.wg.Add(1)
-		app.jobs <- Job{File: f}
+		select {
+		case app.jobs <- job:
+			pending--
+		case <-ctx.Done():
+			app.wg.Done()
+			break feed
+		}
 	}
 
 	close(app.jobs)
 	app.wg.Wait()
+
+	code := exitOK
+	if err := manifest.flush(); err != nil {
+		fmt.Println("⚠ manifesto:", err)
+	}
+	if ctx.Err() != nil {
+		fmt.Printf("⚠ execução interrompida; %d capturas não foram iniciadas\n", pending)
+		code = exitFailed
+	}
+	// índices do banco da campanha, depois de todas as inserções
+	if err := closeSQLiteDBs(); err != nil {
+		fmt.Println("❌ sqlite:", err)
+		code = exitFailed
+	}
+	if err := finishDatasets(); err != nil {
+		fmt.Println("❌ dataset:", err)
+		code = exitFailed
+	}
+
+	fmt.Println()
+	if printSummary(os.Stdout, app.results, upToDate) > 0 {
+		code = exitFailed
+	}
+	return code
 }
@@ -0,0 +1,266 @@
+package main
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"sync"
+	"time"
+)
+
+//
+// ---------------- MANIFESTO ----------------
+//
+
+// manifestName é o manifesto da execução, gravado no diretório das saídas.
+const manifestName = "pshark-manifest.json"
+
+// manifestSaveInterval espaça as gravações do manifesto: com milhares de
+// capturas, regravar o JSON inteiro a cada uma custaria O(n²). flush grava
+// o que faltar no fim da execução.
+const manifestSaveInterval = 10 * time.Second
+
+// Manifest registra as capturas já convertidas, para que uma nova execução
+// sobre o mesmo diretório só processe as novas ou alteradas.
+type Manifest struct {
+	path  string
+	mu    sync.Mutex
+	dirty bool      // alterações ainda não gravadas
+	saved time.Time // última gravação
+
+	Files map[string]*ManifestEntry `json:"files"` // caminho absoluto da captura
+}
+
+// ManifestEntry é uma captura e, por datagroup, a configuração e as saídas
+// da última conversão. Execuções com -g diferentes não se invalidam.
+type ManifestEntry struct {
+	Size    int64                     `json:"size"`
+	ModTime time.Time                 `json:"mtime"`
+	Hash    string                    `json:"sha256"`
+	Groups  map[string]*ManifestGroup `json:"datagroups"`
+}
+
+type ManifestGroup struct {
+	Config   string    `json:"config_hash"`
+	Outputs  []string  `json:"outputs"`
+	Rows     int       `json:"rows"`
+	Finished time.Time `json:"finished"`
+}
+
+// sourceState é o conteúdo da captura que gerou as saídas: tamanho e hash
+// dos bytes lidos durante a conversão e a data antes da leitura.
+type sourceState struct {
+	Size    int64
+	ModTime time.Time
+	Hash    string
+}
+
+// loadManifest lê o manifesto de path; sem o arquivo, começa vazio.
+func loadManifest(path string) (*Manifest, error) {
+	m := &Manifest{path: path, Files: map[string]*ManifestEntry{}}
+	data, err := os.ReadFile(path)
+	if os.IsNotExist(err) {
+		return m, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	if err := json.Unmarshal(data, m); err != nil {
+		return nil, fmt.Errorf("%s: %w", path, err)
+	}
+	if m.Files == nil {
+		m.Files = map[string]*ManifestEntry{}
+	}
+	return m, nil
+}
+
+// upToDate diz se a captura já foi convertida em todos os datagroups de
+// configs (nome -> configHash) com a mesma configuração e as saídas ainda
+// existem. O hash do conteúdo só é calculado quando tamanho ou data mudaram.
+func (m *Manifest) upToDate(file string, configs map[string]string) bool {
+	key, err := manifestKey(file)
+	if err != nil {
+		return false
+	}
+	st, err := os.Stat(file)
+	if err != nil {
+		return false
+	}
+	m.mu.Lock()
+	e, ok := m.Files[key]
+	m.mu.Unlock()
+	if !ok {
+		return false
+	}
+	for name, config := range configs {
+		g := e.Groups[name]
+		if g == nil || g.Config != config {
+			return false
+		}
+		for _, out := range g.Outputs {
+			if _, err := os.Stat(out); err != nil {
+				return false
+			}
+		}
+	}
+	if st.Size() == e.Size && st.ModTime().Equal(e.ModTime) {
+		return true
+	}
+	hash, err := hashFile(file)
+	if err != nil || hash != e.Hash {
+		return false
+	}
+	// só a data mudou (cópia, touch): atualiza para não calcular de novo
+	m.mu.Lock()
+	e.Size, e.ModTime = st.Size(), st.ModTime()
+	m.dirty = true
+	m.mu.Unlock()
+	return true
+}
+
+// record registra os datagroups convertidos de uma captura. src vem da
+// leitura: uma captura que cresceu durante a conversão não fica registrada
+// com o conteúdo novo. Os demais datagroups da entrada são mantidos se o
+// conteúdo não mudou. O manifesto é gravado no máximo a cada
+// manifestSaveInterval.
+func (m *Manifest) record(file string, configs map[string]string, src sourceState, outputs map[string][]string, rows map[string]int) error {
+	key, err := manifestKey(file)
+	if err != nil {
+		return err
+	}
+	now := time.Now().UTC()
+	m.mu.Lock()
+	e := m.Files[key]
+	if e == nil || e.Hash != src.Hash || e.Groups == nil {
+		e = &ManifestEntry{Groups: map[string]*ManifestGroup{}}
+		m.Files[key] = e
+	}
+	e.Size, e.ModTime, e.Hash = src.Size, src.ModTime, src.Hash
+	for name, n := range rows {
+		e.Groups[name] = &ManifestGroup{
+			Config:   configs[name],
+			Outputs:  outputs[name],
+			Rows:     n,
+			Finished: now,
+		}
+	}
+	m.dirty = true
+	due := time.Since(m.saved) >= manifestSaveInterval
+	m.mu.Unlock()
+	if !due {
+		return nil
+	}
+	return m.flush()
+}
+
+// flush grava as alterações pendentes num arquivo temporário e renomeia,
+// para não deixar um manifesto pela metade.
+func (m *Manifest) flush() error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if !m.dirty {
+		return nil
+	}
+
+	data, err := json.MarshalIndent(m, "", "  ")
+	if err != nil {
+		return err
+	}
+	if dir := filepath.Dir(m.path); dir != "." {
+		if err := os.MkdirAll(dir, 0o755); err != nil {
+			return err
+		}
+	}
+	tmp := m.path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o644); err != nil {
+		return err
+	}
+	if err := os.Rename(tmp, m.path); err != nil {
+		return err
+	}
+	m.dirty, m.saved = false, time.Now()
+	return nil
+}
+
+func manifestKey(file string) (string, error) {
+	abs, err := filepath.Abs(file)
+	if err != nil {
+		return "", err
+	}
+	return filepath.ToSlash(abs), nil
+}
+
+// sourceID identifica a captura em nomes de arquivo e de tabela: o início
+// do hash do caminho absoluto.
+func sourceID(file string) string {
+	key, err := manifestKey(file)
+	if err != nil {
+		key = file
+	}
+	sum := sha256.Sum256([]byte(key))
+	return hex.EncodeToString(sum[:6])
+}
+
+func hashFile(path string) (string, error) {
+	f, err := os.Open(path)
+	if err != nil {
+		return "", err
+	}
+	defer f.Close()
+	h := sha256.New()
+	if _, err := io.Copy(h, f); err != nil {
+		return "", err
+	}
+	return hex.EncodeToString(h.Sum(nil)), nil
+}
+
+// configHashes identifica, por datagroup, a configuração que produz as
+// saídas: [tshark], [decoder] sem o timeout, [[frame]], o próprio datagroup,
+// as saídas depois de -o, -csv e -out e as especificações ASTERIX. Mudar
+// um datagroup não invalida os outros.
+func configHashes(cfg Config, groups []*Group) (map[string]string, error) {
+	specs := sha256.New()
+	if cfg.Decoder.Specs != "" {
+		err := filepath.WalkDir(cfg.Decoder.Specs, func(path string, d os.DirEntry, err error) error {
+			if err != nil || d.IsDir() {
+				return err
+			}
+			spec, err := os.ReadFile(path)
+			if err != nil {
+				return err
+			}
+			fmt.Fprintf(specs, "%s\x00", filepath.ToSlash(path))
+			specs.Write(spec)
+			return nil
+		})
+		if err != nil && !os.IsNotExist(err) {
+			return nil, err
+		}
+	}
+	specsSum := specs.Sum(nil)
+
+	decoder := cfg.Decoder
+	decoder.Timeout = "" // não muda as saídas
+	hashes := map[string]string{}
+	for _, g := range groups {
+		data, err := json.Marshal(struct {
+			Tshark    TShark
+			Decoder   DecoderConfig
+			Frame     []DataItem
+			Datagroup Datagroup
+			Output    OutputConfig
+		}{cfg.Tshark, decoder, cfg.Frame, cfg.Datagroup[g.Name], g.Output})
+		if err != nil {
+			return nil, err
+		}
+		h := sha256.New()
+		h.Write(data)
+		h.Write(specsSum)
+		hashes[g.Name] = hex.EncodeToString(h.Sum(nil))
+	}
+	return hashes, nil
+}
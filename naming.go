@@ -0,0 +1,298 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"regexp"
+	"strings"
+)
+
+//
+// ---------------- NOMES DE SAÍDA ----------------
+//
+
+// políticas para saídas que já existem (exists)
+const (
+	ExistsOverwrite = "overwrite" // padrão
+	ExistsSkip      = "skip"      // não grava essa saída
+	ExistsFail      = "fail"      // interrompe o arquivo
+)
+
+// o subdiretório da captura evita que radar1/x.pcap e radar2/x.pcap
+// gravem a mesma saída
+const defaultNameTemplate = "{dir}/{stem}.{datagroup}"
+
+var namePlaceholder = regexp.MustCompile(`\{[a-z]*\}`)
+
+// nameVars são os valores do modelo de nome. Date, SAC e SIC vêm de cada
+// linha; os outros, do arquivo de captura.
+type nameVars struct {
+	Dir       string // subdiretório da captura em relação ao diretório de entrada
+	Stem      string
+	Datagroup string
+	Date      string
+	SAC       string
+	SIC       string
+}
+
+func (o OutputConfig) nameTemplate() string {
+	if o.Name == "" {
+		return defaultNameTemplate
+	}
+	return o.Name
+}
+
+func validNameTemplate(tmpl string) error {
+	for _, p := range namePlaceholder.FindAllString(tmpl, -1) {
+		switch p {
+		case "{dir}", "{stem}", "{datagroup}", "{date}", "{sac}", "{sic}":
+		default:
+			return fmt.Errorf("variável desconhecida %s no nome", p)
+		}
+	}
+	return nil
+}
+
+func validExists(s string) bool {
+	switch s {
+	case "", ExistsOverwrite, ExistsSkip, ExistsFail:
+		return true
+	}
+	return false
+}
+
+// rowDependent diz se o modelo usa valores das linhas.
+func rowDependent(tmpl string) bool {
+	return strings.Contains(tmpl, "{date}") || strings.Contains(tmpl, "{sac}") || strings.Contains(tmpl, "{sic}")
+}
+
+// outputPath monta <dir>/<modelo><ext>. Sem subdiretório, "{dir}/" some
+// do modelo, para o nome não virar um caminho absoluto.
+func (o OutputConfig) outputPath(v nameVars, ext string) string {
+	tmpl := o.nameTemplate()
+	if v.Dir == "" {
+		tmpl = strings.ReplaceAll(tmpl, "{dir}/", "")
+	}
+	name := strings.NewReplacer(
+		"{dir}", v.Dir,
+		"{stem}", v.Stem,
+		"{datagroup}", v.Datagroup,
+		"{date}", v.Date,
+		"{sac}", v.SAC,
+		"{sic}", v.SIC,
+	).Replace(tmpl)
+	return filepath.Join(o.Dir, filepath.FromSlash(name)) + ext
+}
+
+// checkExisting aplica a política exists. skip = true quando a saída deve
+// ser ignorada.
+func checkExisting(path, policy string) (skip bool, err error) {
+	if _, err := os.Stat(path); os.IsNotExist(err) {
+		return false, nil
+	}
+	switch policy {
+	case ExistsSkip:
+		return true, nil
+	case ExistsFail:
+		return false, fmt.Errorf("%s já existe", path)
+	}
+	return false, nil
+}
+
+// openNamed aplica a política exists, cria o diretório e abre o sink.
+// Retorna nil, nil quando a saída é ignorada.
+func openNamed(sf sinkFormat, t sinkTarget) (Sink, error) {
+	skip, err := checkExisting(t.Path, t.Output.Exists)
+	if err != nil || skip {
+		return nil, err
+	}
+	if dir := filepath.Dir(t.Path); dir != "." {
+		if err := os.MkdirAll(dir, 0o755); err != nil {
+			return nil, err
+		}
+	}
+	return sf.open(t)
+}
+
+// routeSink atende modelos com {date}, {sac} ou {sic}: o nome é calculado
+// a cada linha e cada nome novo abre o seu sink.
+type routeSink struct {
+	format  sinkFormat
+	target  sinkTarget
+	vars    nameVars
+	timeCol int
+	sacCol  int
+	sicCol  int
+	sinks   map[string]Sink // nil = ignorada pela política exists
+	paths   []string
+}
+
+func newRouteSink(sf sinkFormat, t sinkTarget, v nameVars) *routeSink {
+	r := &routeSink{
+		format:  sf,
+		target:  t,
+		vars:    v,
+		timeCol: timeColumn(t.Fields),
+		sinks:   map[string]Sink{},
+	}
+	r.sacCol, r.sicCol = sacSicColumns(t.Fields)
+	return r
+}
+
+func (r *routeSink) WriteRow(values []any) error {
+	v := r.vars
+	v.Date, v.SAC, v.SIC = "unknown", "unknown", "unknown"
+	if r.timeCol >= 0 {
+		if t, ok := rowTime(values[r.timeCol]); ok {
+			v.Date = t.UTC().Format("2006-01-02")
+		}
+	}
+	if r.sacCol >= 0 && values[r.sacCol] != nil {
+		v.SAC = formatValue(values[r.sacCol])
+	}
+	if r.sicCol >= 0 && values[r.sicCol] != nil {
+		v.SIC = formatValue(values[r.sicCol])
+	}
+
+	path := r.target.Output.outputPath(v, r.format.ext)
+	s, ok := r.sinks[path]
+	if !ok {
+		t := r.target
+		t.Path = path
+		var err error
+		if s, err = openNamed(r.format, t); err != nil {
+			return err
+		}
+		r.sinks[path] = s
+		if s != nil {
+			r.paths = append(r.paths, path)
+		}
+	}
+	if s == nil {
+		return nil
+	}
+	return s.WriteRow(values)
+}
+
+func (r *routeSink) Close() error {
+	var first error
+	for _, s := range r.sinks {
+		if s == nil {
+			continue
+		}
+		if err := s.Close(); err != nil && first == nil {
+			first = err
+		}
+	}
+	return first
+}
+
+func (r *routeSink) Abort() {
+	for _, s := range r.sinks {
+		if s != nil {
+			s.Abort()
+		}
+	}
+}
+
+func (r *routeSink) Paths() []string {
+	return r.paths
+}
+
+// underDir coloca sob dir o banco da campanha e a raiz do dataset, quando
+// são relativos.
+func (o OutputConfig) underDir() OutputConfig {
+	if o.Dir == "" {
+		return o
+	}
+	if o.SQLite.Path != "" && !filepath.IsAbs(o.SQLite.Path) {
+		o.SQLite.Path = filepath.Join(o.Dir, o.SQLite.Path)
+	}
+	if root := o.Dataset.root(); !filepath.IsAbs(root) {
+		o.Dataset.Root = filepath.Join(o.Dir, root)
+	}
+	return o
+}
+
+// sacSicColumns retorna as colunas dos itens I0xx/010 SAC e SIC, ou -1.
+func sacSicColumns(fields []DataItem) (sac, sic int) {
+	sac, sic = -1, -1
+	for i, f := range fields {
+		switch {
+		case strings.HasSuffix(f.Field, "_010_SAC"):
+			sac = i
+		case strings.HasSuffix(f.Field, "_010_SIC"):
+			sic = i
+		}
+	}
+	return sac, sic
+}
+
+// checkCollisions falha se duas capturas gravariam a mesma saída: com vários
+// workers, a última a terminar sobrescreveria as outras. {date}, {sac} e
+// {sic} ficam como estão, já que duas capturas podem ter os mesmos valores.
+func checkCollisions(jobs []Job, groups []*Group) error {
+	seen := map[string]string{}
+	for _, job := range jobs {
+		v := nameVars{Dir: job.Dir, Stem: captureStem(job.File), Date: "{date}", SAC: "{sac}", SIC: "{sic}"}
+		for _, g := range groups {
+			v.Datagroup = g.Name
+			for _, format := range g.Output.Formats {
+				if ownPath(format, g.Output) {
+					continue
+				}
+				path := g.Output.outputPath(v, sinkFormats[format].ext)
+				if other, ok := seen[path]; ok && other != job.File {
+					return fmt.Errorf("%s e %s gravariam %s; use {dir} ou {stem} em output.name", other, job.File, path)
+				}
+				seen[path] = job.File
+			}
+		}
+	}
+	return nil
+}
+
+// commonDir é o maior diretório que contém todos os arquivos, base do
+// {dir} das capturas de -f e -list.
+func commonDir(files []string) string {
+	var common string
+	for i, f := range files {
+		dir, err := filepath.Abs(filepath.Dir(f))
+		if err != nil {
+			return ""
+		}
+		if i == 0 {
+			common = dir
+			continue
+		}
+		for common != dir && !strings.HasPrefix(dir, common+string(filepath.Separator)) {
+			parent := filepath.Dir(common)
+			if parent == common {
+				break
+			}
+			common = parent
+		}
+	}
+	return common
+}
+
+// inputDir é o {dir} de uma captura: o caminho relativo ao diretório de
+// entrada (-d) ou ao diretório comum das capturas de -f e -list.
+func inputDir(root, file string) string {
+	if root == "" {
+		return ""
+	}
+	abs, err := filepath.Abs(filepath.Dir(file))
+	if err != nil {
+		return ""
+	}
+	if root, err = filepath.Abs(root); err != nil {
+		return ""
+	}
+	rel, err := filepath.Rel(root, abs)
+	if err != nil || rel == "." || strings.HasPrefix(rel, "..") {
+		return ""
+	}
+	return filepath.ToSlash(rel)
+}
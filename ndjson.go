@@ -0,0 +1,115 @@
+package main
+
+import (
+	"bufio"
+	"encoding/json"
+	"math"
+	"strconv"
+
+	"github.com/apache/arrow/go/v14/arrow"
+)
+
+//
+// ---------------- NDJSON ----------------
+//
+
+type NDJSONOptions struct {
+	OmitNulls *bool `toml:"omit_nulls"` // não grava chaves com null (padrão false)
+}
+
+func (o NDJSONOptions) merge(over NDJSONOptions) NDJSONOptions {
+	if over.OmitNulls != nil {
+		o.OmitNulls = over.OmitNulls
+	}
+	return o
+}
+
+// NDJSONWriter grava um objeto JSON por linha, com as chaves na ordem das
+// colunas.
+type NDJSONWriter struct {
+	file      *outputFile
+	w         *bufio.Writer
+	keys      [][]byte // labels já codificados, com ':'
+	omitNulls bool
+	buf       []byte
+}
+
+func newNDJSONWriter(path string, fields []DataItem, opts NDJSONOptions) (*NDJSONWriter, error) {
+	f, err := createOutput(path)
+	if err != nil {
+		return nil, err
+	}
+	w := &NDJSONWriter{
+		file:      f,
+		w:         bufio.NewWriterSize(f, 1<<20),
+		keys:      make([][]byte, len(fields)),
+		omitNulls: opts.OmitNulls != nil && *opts.OmitNulls,
+	}
+	for i, fd := range fields {
+		k, _ := json.Marshal(fd.Label)
+		w.keys[i] = append(k, ':')
+	}
+	return w, nil
+}
+
+func (w *NDJSONWriter) WriteRow(values []any) error {
+	b := append(w.buf[:0], '{')
+	first := true
+	for i, v := range values {
+		if v == nil && w.omitNulls {
+			continue
+		}
+		if !first {
+			b = append(b, ',')
+		}
+		first = false
+		b = append(b, w.keys[i]...)
+		b = appendJSON(b, v)
+	}
+	b = append(b, '}', '\n')
+	w.buf = b
+	_, err := w.w.Write(b)
+	return err
+}
+
+func (w *NDJSONWriter) Close() error {
+	return w.file.finish(w.w.Flush())
+}
+
+func (w *NDJSONWriter) Abort() {
+	w.file.abort()
+}
+
+// appendJSON codifica um valor convertido. Timestamps saem em RFC 3339 e
+// NaN/Inf, que o JSON não representa, como null.
+func appendJSON(b []byte, v any) []byte {
+	switch x := v.(type) {
+	case nil:
+		return append(b, "null"...)
+	case string:
+		s, _ := json.Marshal(x)
+		return append(b, s...)
+	case float32:
+		if math.IsNaN(float64(x)) || math.IsInf(float64(x), 0) {
+			return append(b, "null"...)
+		}
+		return strconv.AppendFloat(b, float64(x), 'g', -1, 32)
+	case float64:
+		if math.IsNaN(x) || math.IsInf(x, 0) {
+			return append(b, "null"...)
+		}
+		return strconv.AppendFloat(b, x, 'g', -1, 64)
+	case arrow.Timestamp:
+		return strconv.AppendQuote(b, formatValue(x))
+	case []any:
+		b = append(b, '[')
+		for i, e := range x {
+			if i > 0 {
+				b = append(b, ',')
+			}
+			b = appendJSON(b, e)
+		}
+		return append(b, ']')
+	}
+	return append(b, formatValue(v)...)
+}
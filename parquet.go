@@ -0,0 +1,210 @@
+package main
+
+import (
+	"fmt"
+
+	"github.com/apache/arrow/go/v14/parquet"
+	"github.com/apache/arrow/go/v14/parquet/compress"
+	"github.com/apache/arrow/go/v14/parquet/pqarrow"
+)
+
+//
+// ---------------- PARQUET ----------------
+//
+
+// ParquetOptions vem de [output.parquet] e pode ser sobreposta por
+// [datagroup.x.output.parquet]. Campos vazios mantêm o padrão.
+type ParquetOptions struct {
+	Codec        string          `toml:"codec"`              // snappy (padrão), zstd, gzip, lz4, lz4_raw, brotli, none
+	Level        *int            `toml:"level"`              // nível de compressão (zstd, gzip, brotli)
+	RowGroupSize int64           `toml:"row_group_size"`     // linhas por row group
+	DataPageSize int64           `toml:"data_page_size"`     // bytes por página
+	Dictionary   *bool           `toml:"dictionary"`         // dicionário em todas as colunas (padrão true)
+	DictColumns  map[string]bool `toml:"dictionary_columns"` // por label, ex. { CALLSIGN = true, TIMESTAMP = false }
+	Statistics   *bool           `toml:"statistics"`         // padrão true
+	Version      string          `toml:"version"`            // 1.0, 2.4 ou 2.6 (padrão)
+	Batch        int             `toml:"batch"`              // linhas por lote Arrow (padrão 1024)
+}
+
+var parquetCodecs = map[string]compress.Compression{
+	"":        compress.Codecs.Snappy,
+	"snappy":  compress.Codecs.Snappy,
+	"zstd":    compress.Codecs.Zstd,
+	"gzip":    compress.Codecs.Gzip,
+	"lz4":     compress.Codecs.Lz4,
+	"lz4_raw": compress.Codecs.Lz4Raw,
+	"brotli":  compress.Codecs.Brotli,
+	"none":    compress.Codecs.Uncompressed,
+}
+
+var parquetVersions = map[string]parquet.Version{
+	"":    parquet.V2_6,
+	"1.0": parquet.V1_0,
+	"2.4": parquet.V2_4,
+	"2.6": parquet.V2_6,
+}
+
+// merge aplica sobre o a configuração do datagroup.
+func (o ParquetOptions) merge(over ParquetOptions) ParquetOptions {
+	if over.Codec != "" {
+		o.Codec = over.Codec
+	}
+	if over.Level != nil {
+		o.Level = over.Level
+	}
+	if over.RowGroupSize != 0 {
+		o.RowGroupSize = over.RowGroupSize
+	}
+	if over.DataPageSize != 0 {
+		o.DataPageSize = over.DataPageSize
+	}
+	if over.Dictionary != nil {
+		o.Dictionary = over.Dictionary
+	}
+	if over.DictColumns != nil {
+		cols := map[string]bool{}
+		for k, v := range o.DictColumns {
+			cols[k] = v
+		}
+		for k, v := range over.DictColumns {
+			cols[k] = v
+		}
+		o.DictColumns = cols
+	}
+	if over.Statistics != nil {
+		o.Statistics = over.Statistics
+	}
+	if over.Version != "" {
+		o.Version = over.Version
+	}
+	if over.Batch != 0 {
+		o.Batch = over.Batch
+	}
+	return o
+}
+
+func (o ParquetOptions) validate() error {
+	if _, ok := parquetCodecs[o.Codec]; !ok {
+		return fmt.Errorf("codec inválido %q", o.Codec)
+	}
+	if _, ok := parquetVersions[o.Version]; !ok {
+		return fmt.Errorf("version inválida %q", o.Version)
+	}
+	if o.RowGroupSize < 0 || o.DataPageSize < 0 || o.Batch < 0 {
+		return fmt.Errorf("row_group_size, data_page_size e batch não podem ser negativos")
+	}
+	return nil
+}
+
+func (o ParquetOptions) batch() int {
+	if o.Batch > 0 {
+		return o.Batch
+	}
+	return 1024
+}
+
+// writerProperties monta as propriedades do writer. Os nomes de coluna do
+// parquet são os labels.
+func (o ParquetOptions) writerProperties(fields []DataItem) *parquet.WriterProperties {
+	opts := []parquet.WriterProperty{
+		parquet.WithCompression(parquetCodecs[o.Codec]),
+		parquet.WithVersion(parquetVersions[o.Version]),
+	}
+	if o.Level != nil {
+		opts = append(opts, parquet.WithCompressionLevel(*o.Level))
+	}
+	if o.RowGroupSize > 0 {
+		opts = append(opts, parquet.WithMaxRowGroupLength(o.RowGroupSize))
+	}
+	if o.DataPageSize > 0 {
+		opts = append(opts, parquet.WithDataPageSize(o.DataPageSize))
+	}
+	if o.Dictionary != nil {
+		opts = append(opts, parquet.WithDictionaryDefault(*o.Dictionary))
+	}
+	for _, f := range fields {
+		if on, ok := o.DictColumns[f.Label]; ok {
+			opts = append(opts, parquet.WithDictionaryFor(parquetLeaf(f), on))
+		}
+	}
+	if o.Statistics != nil {
+		opts = append(opts, parquet.WithStats(*o.Statistics))
+	}
+	return parquet.NewWriterProperties(opts...)
+}
+
+// parquetLeaf é o caminho da coluna física do label: o dicionário de uma
+// list<T> vale para os elementos (LABEL.list.element).
+func parquetLeaf(f DataItem) string {
+	if _, ok := listElem(f.Type); ok {
+		return f.Label + ".list.element"
+	}
+	return f.Label
+}
+
+type ParquetWriter struct {
+	file     *outputFile
+	writer   *pqarrow.FileWriter
+	batch    *rowBatch
+	buffered bool // row groups de row_group_size linhas em vez de um por lote
+}
+
+func newParquetWriter(path string, fields []DataItem, opts ParquetOptions) (*ParquetWriter, error) {
+	batch := newRowBatch(fields, opts.batch())
+
+	file, err := createOutput(path)
+	if err != nil {
+		return nil, err
+	}
+
+	writer, err := pqarrow.NewFileWriter(
+		batch.schema,
+		file,
+		opts.writerProperties(fields),
+		// grava o schema Arrow para preservar os metadados (unidades)
+		pqarrow.NewArrowWriterProperties(pqarrow.WithStoreSchema()),
+	)
+	if err != nil {
+		file.abort()
+		return nil, err
+	}
+
+	return &ParquetWriter{
+		file:     file,
+		writer:   writer,
+		batch:    batch,
+		buffered: opts.RowGroupSize > 0,
+	}, nil
+}
+
+func (p *ParquetWriter) WriteRow(values []any) error {
+	if p.batch.append(values) {
+		return p.flush()
+	}
+	return nil
+}
+
+func (p *ParquetWriter) flush() error {
+	record := p.batch.record()
+	defer record.Release()
+
+	if p.buffered {
+		return p.writer.WriteBuffered(record)
+	}
+	return p.writer.Write(record)
+}
+
+func (p *ParquetWriter) Close() error {
+	if p.batch.rows > 0 {
+		if err := p.flush(); err != nil {
+			p.writer.Close()
+			return p.file.finish(err)
+		}
+	}
+	return p.file.finish(p.writer.Close())
+}
+
+// Abort descarta o arquivo sem gravar o footer.
+func (p *ParquetWriter) Abort() {
+	p.file.abort()
+}
@@ -0,0 +1,544 @@
+package main
+
+import (
+	"bufio"
+	"encoding/binary"
+	"fmt"
+	"io"
+	"math"
+	"net/netip"
+	"sort"
+	"strconv"
+	"time"
+)
+
+//
+// ---------------- PCAP ----------------
+//
+
+const (
+	linkTypeNull     = 0
+	linkTypeEthernet = 1
+	linkTypeRawDLT   = 12
+	linkTypeRaw      = 101
+	linkTypeLinuxSLL = 113
+	linkTypeIPv4     = 228
+	linkTypeSLL2     = 276
+)
+
+type CapFrame struct {
+	Time     time.Time
+	LinkType uint32
+	Data     []byte
+}
+
+// CaptureReader é implementado pelos leitores de pcap e pcapng.
+type CaptureReader interface {
+	// Next retorna o próximo frame ou io.EOF no fim do arquivo.
+	Next() (CapFrame, error)
+}
+
+// maxFrameSize limita caplen para não alocar lixo em arquivos corrompidos.
+const maxFrameSize = 1 << 18
+
+// OpenCapture detecta o formato pelo magic number.
+func OpenCapture(r io.Reader) (CaptureReader, error) {
+	br := bufio.NewReaderSize(r, 1<<20)
+	magic, err := br.Peek(4)
+	if err != nil {
+		return nil, fmt.Errorf("cabeçalho de captura: %w", err)
+	}
+	if binary.LittleEndian.Uint32(magic) == 0x0a0d0d0a {
+		return newPcapngReader(br), nil
+	}
+	return NewPcapReader(br)
+}
+
+// PcapReader lê o formato pcap clássico (micro e nanossegundos).
+type PcapReader struct {
+	r        *bufio.Reader
+	order    binary.ByteOrder
+	nano     bool
+	linkType uint32
+	hdr      [16]byte
+}
+
+func NewPcapReader(r io.Reader) (*PcapReader, error) {
+	br, ok := r.(*bufio.Reader)
+	if !ok {
+		br = bufio.NewReaderSize(r, 1<<20)
+	}
+
+	var gh [24]byte
+	if _, err := io.ReadFull(br, gh[:]); err != nil {
+		return nil, fmt.Errorf("cabeçalho pcap: %w", err)
+	}
+
+	p := &PcapReader{r: br}
+	switch binary.LittleEndian.Uint32(gh[0:4]) {
+	case 0xa1b2c3d4:
+		p.order = binary.LittleEndian
+	case 0xd4c3b2a1:
+		p.order = binary.BigEndian
+	case 0xa1b23c4d:
+		p.order, p.nano = binary.LittleEndian, true
+	case 0x4d3cb2a1:
+		p.order, p.nano = binary.BigEndian, true
+	default:
+		return nil, fmt.Errorf("formato de captura não suportado (magic %x)", gh[0:4])
+	}
+	// os 4 bits altos podem carregar o FCS length
+	p.linkType = p.order.Uint32(gh[20:24]) & 0x0fffffff
+	return p, nil
+}
+
+func (p *PcapReader) Next() (CapFrame, error) {
+	if _, err := io.ReadFull(p.r, p.hdr[:]); err != nil {
+		if err == io.ErrUnexpectedEOF {
+			return CapFrame{}, fmt.Errorf("registro pcap truncado")
+		}
+		return CapFrame{}, err
+	}
+	sec := p.order.Uint32(p.hdr[0:4])
+	frac := p.order.Uint32(p.hdr[4:8])
+	caplen := p.order.Uint32(p.hdr[8:12])
+	if caplen > maxFrameSize {
+		return CapFrame{}, fmt.Errorf("registro pcap inválido (caplen %d)", caplen)
+	}
+
+	data := make([]byte, caplen)
+	if _, err := io.ReadFull(p.r, data); err != nil {
+		return CapFrame{}, fmt.Errorf("registro pcap truncado")
+	}
+
+	nsec := int64(frac) * 1000
+	if p.nano {
+		nsec = int64(frac)
+	}
+	return CapFrame{
+		Time:     time.Unix(int64(sec), nsec).UTC(),
+		LinkType: p.linkType,
+		Data:     data,
+	}, nil
+}
+
+//
+// ---------------- PCAPNG ----------------
+//
+
+const (
+	blockSHB = 0x0a0d0d0a
+	blockIDB = 0x00000001
+	blockSPB = 0x00000003
+	blockEPB = 0x00000006
+)
+
+type pcapngInterface struct {
+	linkType uint32
+	snaplen  uint32
+	tsres    uint8 // if_tsresol: bit alto = potência de 2, senão de 10
+	tsoffset int64 // if_tsoffset em segundos
+}
+
+// PcapngReader lê seções, interfaces e os blocos EPB/SPB do pcapng.
+type PcapngReader struct {
+	r      *bufio.Reader
+	order  binary.ByteOrder
+	ifaces []pcapngInterface
+}
+
+func newPcapngReader(r *bufio.Reader) *PcapngReader {
+	return &PcapngReader{r: r, order: binary.LittleEndian}
+}
+
+func (p *PcapngReader) Next() (CapFrame, error) {
+	for {
+		var hdr [8]byte
+		if _, err := io.ReadFull(p.r, hdr[:]); err != nil {
+			if err == io.ErrUnexpectedEOF {
+				return CapFrame{}, fmt.Errorf("bloco pcapng truncado")
+			}
+			return CapFrame{}, err
+		}
+
+		btype := p.order.Uint32(hdr[0:4])
+		if btype == blockSHB {
+			// a ordem dos bytes é definida pelo byte-order magic de cada seção
+			var bom [4]byte
+			if _, err := io.ReadFull(p.r, bom[:]); err != nil {
+				return CapFrame{}, fmt.Errorf("bloco pcapng truncado")
+			}
+			switch binary.LittleEndian.Uint32(bom[:]) {
+			case 0x1a2b3c4d:
+				p.order = binary.LittleEndian
+			case 0x4d3c2b1a:
+				p.order = binary.BigEndian
+			default:
+				return CapFrame{}, fmt.Errorf("pcapng: byte-order magic inválido %x", bom)
+			}
+			p.ifaces = p.ifaces[:0]
+			blen := p.order.Uint32(hdr[4:8])
+			if blen < 16 || blen%4 != 0 {
+				return CapFrame{}, fmt.Errorf("pcapng: tamanho de SHB inválido %d", blen)
+			}
+			if _, err := p.r.Discard(int(blen) - 12); err != nil {
+				return CapFrame{}, fmt.Errorf("bloco pcapng truncado")
+			}
+			continue
+		}
+
+		blen := p.order.Uint32(hdr[4:8])
+		if blen < 12 || blen%4 != 0 || blen > maxFrameSize+64 {
+			return CapFrame{}, fmt.Errorf("pcapng: tamanho de bloco inválido %d", blen)
+		}
+		body := make([]byte, blen-8)
+		if _, err := io.ReadFull(p.r, body); err != nil {
+			return CapFrame{}, fmt.Errorf("bloco pcapng truncado")
+		}
+		body = body[:len(body)-4] // tamanho repetido no fim do bloco
+
+		switch btype {
+		case blockIDB:
+			if len(body) < 8 {
+				return CapFrame{}, fmt.Errorf("pcapng: IDB truncado")
+			}
+			iface := pcapngInterface{
+				linkType: uint32(p.order.Uint16(body[0:2])),
+				snaplen:  p.order.Uint32(body[4:8]),
+				tsres:    6,
+			}
+			p.readIfaceOptions(&iface, body[8:])
+			p.ifaces = append(p.ifaces, iface)
+
+		case blockEPB:
+			if len(body) < 20 {
+				return CapFrame{}, fmt.Errorf("pcapng: EPB truncado")
+			}
+			id := p.order.Uint32(body[0:4])
+			if int(id) >= len(p.ifaces) {
+				return CapFrame{}, fmt.Errorf("pcapng: interface %d não declarada", id)
+			}
+			iface := p.ifaces[id]
+			ts := uint64(p.order.Uint32(body[4:8]))<<32 | uint64(p.order.Uint32(body[8:12]))
+			caplen := p.order.Uint32(body[12:16])
+			if int(caplen) > len(body)-20 {
+				return CapFrame{}, fmt.Errorf("pcapng: EPB com caplen inválido %d", caplen)
+			}
+			return CapFrame{
+				Time:     iface.timestamp(ts),
+				LinkType: iface.linkType,
+				Data:     body[20 : 20+caplen],
+			}, nil
+
+		case blockSPB:
+			// SPB não tem timestamp e sempre se refere à primeira interface
+			if len(body) < 4 || len(p.ifaces) == 0 {
+				return CapFrame{}, fmt.Errorf("pcapng: SPB inválido")
+			}
+			iface := p.ifaces[0]
+			caplen := p.order.Uint32(body[0:4])
+			if iface.snaplen > 0 && caplen > iface.snaplen {
+				caplen = iface.snaplen
+			}
+			if int(caplen) > len(body)-4 {
+				caplen = uint32(len(body) - 4)
+			}
+			return CapFrame{
+				LinkType: iface.linkType,
+				Data:     body[4 : 4+caplen],
+			}, nil
+		}
+		// demais blocos (NRB, ISB, custom...) são ignorados
+	}
+}
+
+func (p *PcapngReader) readIfaceOptions(iface *pcapngInterface, opts []byte) {
+	for len(opts) >= 4 {
+		code := p.order.Uint16(opts[0:2])
+		olen := int(p.order.Uint16(opts[2:4]))
+		if code == 0 || 4+olen > len(opts) {
+			return
+		}
+		val := opts[4 : 4+olen]
+		switch {
+		case code == 9 && olen >= 1:
+			iface.tsres = val[0]
+		case code == 14 && olen >= 8:
+			iface.tsoffset = int64(p.order.Uint64(val))
+		}
+		next := 4 + (olen+3)&^3
+		if next > len(opts) {
+			return
+		}
+		opts = opts[next:]
+	}
+}
+
+func (iface pcapngInterface) timestamp(ts uint64) time.Time {
+	var sec, nsec int64
+	if iface.tsres&0x80 != 0 {
+		units := math.Ldexp(1, int(iface.tsres&0x7f))
+		secs := float64(ts) / units
+		sec = int64(secs)
+		nsec = int64((secs - float64(sec)) * 1e9)
+	} else {
+		exp := int(iface.tsres)
+		units := uint64(math.Pow10(exp))
+		sec = int64(ts / units)
+		rem := ts % units
+		if exp <= 9 {
+			nsec = int64(rem * uint64(math.Pow10(9-exp)))
+		} else {
+			nsec = int64(rem / uint64(math.Pow10(exp-9)))
+		}
+	}
+	return time.Unix(sec+iface.tsoffset, nsec).UTC()
+}
+
+//
+// ---------------- UDP ----------------
+//
+
+// Datagram é o payload UDP de um frame, com os endereços de origem/destino.
+type Datagram struct {
+	Src     netip.Addr
+	Dst     netip.Addr
+	SrcPort uint16
+	DstPort uint16
+	Payload []byte
+}
+
+// Fields retorna os campos de quadro com os nomes do wireshark.
+func (d Datagram) Fields() map[string]string {
+	return map[string]string{
+		"ip.src":      d.Src.String(),
+		"ip.dst":      d.Dst.String(),
+		"udp.srcport": strconv.Itoa(int(d.SrcPort)),
+		"udp.dstport": strconv.Itoa(int(d.DstPort)),
+	}
+}
+
+type fragKey struct {
+	src, dst netip.Addr
+	id       uint16
+}
+
+type fragBuf struct {
+	data  []byte
+	spans []fragSpan // trechos recebidos, ordenados e sem sobreposição
+	total int        // tamanho final, conhecido ao receber o último fragmento
+	last  time.Time
+}
+
+type fragSpan struct{ start, end int }
+
+// add copia só os bytes de data ainda não recebidos: fragmentos repetidos ou
+// sobrepostos mantêm a primeira cópia e não contam duas vezes.
+func (fb *fragBuf) add(offset int, data []byte) {
+	end := offset + len(data)
+	if end > len(fb.data) {
+		grown := make([]byte, end)
+		copy(grown, fb.data)
+		fb.data = grown
+	}
+	pos := offset
+	for _, s := range fb.spans {
+		if s.end <= pos || s.start >= end {
+			continue
+		}
+		if s.start > pos {
+			copy(fb.data[pos:s.start], data[pos-offset:])
+		}
+		pos = max(pos, s.end)
+	}
+	if pos < end {
+		copy(fb.data[pos:end], data[pos-offset:])
+	}
+
+	// junta os trechos que encostam no novo
+	merged := fragSpan{offset, end}
+	var spans []fragSpan
+	for _, s := range fb.spans {
+		if s.end < merged.start || s.start > merged.end {
+			spans = append(spans, s)
+			continue
+		}
+		merged.start, merged.end = min(merged.start, s.start), max(merged.end, s.end)
+	}
+	i := sort.Search(len(spans), func(i int) bool { return spans[i].start > merged.start })
+	spans = append(spans, fragSpan{})
+	copy(spans[i+1:], spans[i:])
+	spans[i] = merged
+	fb.spans = spans
+}
+
+// complete diz se o datagrama inteiro, de 0 a total, já chegou.
+func (fb *fragBuf) complete() bool {
+	return fb.total > 0 && len(fb.spans) == 1 && fb.spans[0].start == 0 && fb.spans[0].end >= fb.total
+}
+
+// fragTimeout descarta remontagens incompletas, em tempo de captura.
+const fragTimeout = 30 * time.Second
+
+// UDPDemux extrai datagramas UDP dos frames, remontando fragmentos IPv4.
+type UDPDemux struct {
+	frags map[fragKey]*fragBuf
+	last  time.Time
+}
+
+func NewUDPDemux() *UDPDemux {
+	return &UDPDemux{frags: map[fragKey]*fragBuf{}}
+}
+
+// Parse retorna o datagrama contido no frame. Fragmentos retornam false
+// até o datagrama estar completo.
+func (d *UDPDemux) Parse(f CapFrame) (Datagram, bool) {
+	ip, ok := linkPayload(f.LinkType, f.Data)
+	if !ok {
+		return Datagram{}, false
+	}
+	return d.parseIPv4(f.Time, ip)
+}
+
+// linkPayload remove o cabeçalho de enlace e retorna o pacote IPv4.
+func linkPayload(linkType uint32, data []byte) ([]byte, bool) {
+	var etype uint16
+	switch linkType {
+	case linkTypeEthernet:
+		if len(data) < 14 {
+			return nil, false
+		}
+		etype = binary.BigEndian.Uint16(data[12:14])
+		data = data[14:]
+		// 802.1Q / 802.1ad (QinQ)
+		for etype == 0x8100 || etype == 0x88a8 || etype == 0x9100 {
+			if len(data) < 4 {
+				return nil, false
+			}
+			etype = binary.BigEndian.Uint16(data[2:4])
+			data = data[4:]
+		}
+	case linkTypeLinuxSLL:
+		if len(data) < 16 {
+			return nil, false
+		}
+		etype = binary.BigEndian.Uint16(data[14:16])
+		data = data[16:]
+	case linkTypeSLL2:
+		if len(data) < 20 {
+			return nil, false
+		}
+		etype = binary.BigEndian.Uint16(data[0:2])
+		data = data[20:]
+	case linkTypeNull:
+		if len(data) < 4 {
+			return nil, false
+		}
+		// família AF_INET na ordem de bytes de quem capturou
+		if binary.LittleEndian.Uint32(data[0:4]) != 2 && binary.BigEndian.Uint32(data[0:4]) != 2 {
+			return nil, false
+		}
+		return data[4:], true
+	case linkTypeRaw, linkTypeRawDLT, linkTypeIPv4:
+		return data, true
+	default:
+		return nil, false
+	}
+	if etype != 0x0800 {
+		return nil, false
+	}
+	return data, true
+}
+
+func (d *UDPDemux) parseIPv4(ts time.Time, ip []byte) (Datagram, bool) {
+	if len(ip) < 20 || ip[0]>>4 != 4 {
+		return Datagram{}, false
+	}
+	ihl := int(ip[0]&0x0f) * 4
+	total := int(binary.BigEndian.Uint16(ip[2:4]))
+	if ip[9] != 17 || ihl < 20 || total < ihl || total > len(ip) {
+		return Datagram{}, false
+	}
+	src := netip.AddrFrom4([4]byte(ip[12:16]))
+	dst := netip.AddrFrom4([4]byte(ip[16:20]))
+	payload := ip[ihl:total]
+
+	flags := binary.BigEndian.Uint16(ip[6:8])
+	more := flags&0x2000 != 0
+	offset := int(flags&0x1fff) * 8
+	if more || offset > 0 {
+		key := fragKey{src: src, dst: dst, id: binary.BigEndian.Uint16(ip[4:6])}
+		var done bool
+		payload, done = d.reassemble(ts, key, offset, more, payload)
+		if !done {
+			return Datagram{}, false
+		}
+	}
+
+	if len(payload) < 8 {
+		return Datagram{}, false
+	}
+	ulen := int(binary.BigEndian.Uint16(payload[4:6]))
+	if ulen < 8 || ulen > len(payload) {
+		return Datagram{}, false
+	}
+	return Datagram{
+		Src:     src,
+		Dst:     dst,
+		SrcPort: binary.BigEndian.Uint16(payload[0:2]),
+		DstPort: binary.BigEndian.Uint16(payload[2:4]),
+		Payload: payload[8:ulen],
+	}, true
+}
+
+// reassemble guarda o fragmento e retorna o payload IP completo quando
+// todos os pedaços chegaram, sem lacunas.
+func (d *UDPDemux) reassemble(ts time.Time, key fragKey, offset int, more bool, data []byte) ([]byte, bool) {
+	if ts.Sub(d.last) > fragTimeout {
+		for k, fb := range d.frags {
+			if ts.Sub(fb.last) > fragTimeout {
+				delete(d.frags, k)
+			}
+		}
+		d.last = ts
+	}
+
+	fb, ok := d.frags[key]
+	if !ok {
+		fb = &fragBuf{}
+		d.frags[key] = fb
+	}
+	fb.last = ts
+
+	end := offset + len(data)
+	if end > 0xffff {
+		delete(d.frags, key)
+		return nil, false
+	}
+	// fragmentos que contradizem o tamanho final invalidam o datagrama
+	bad := fb.total > 0 && end > fb.total
+	if !more {
+		bad = bad || end < len(fb.data) || fb.total > 0 && end != fb.total
+	}
+	if bad {
+		delete(d.frags, key)
+		return nil, false
+	}
+	fb.add(offset, data)
+	if !more {
+		fb.total = end
+	}
+
+	if !fb.complete() {
+		return nil, false
+	}
+	delete(d.frags, key)
+	return fb.data[:fb.total], true
+}
+
+func epochString(t time.Time) string {
+	if t.IsZero() {
+		return ""
+	}
+	return fmt.Sprintf("%d.%09d", t.Unix(), t.Nanosecond())
+}
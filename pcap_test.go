@@ -0,0 +1,103 @@
+package main
+
+import (
+	"bytes"
+	"encoding/binary"
+	"testing"
+	"time"
+)
+
+// udpDatagram monta um datagrama UDP (cabeçalho e payload).
+func udpDatagram(payload []byte) []byte {
+	udp := make([]byte, 8, 8+len(payload))
+	binary.BigEndian.PutUint16(udp[0:2], 1000)
+	binary.BigEndian.PutUint16(udp[2:4], 8600)
+	binary.BigEndian.PutUint16(udp[4:6], uint16(8+len(payload)))
+	return append(udp, payload...)
+}
+
+// ipv4Fragment monta o fragmento IPv4 com os bytes [offset, end) de udp.
+func ipv4Fragment(udp []byte, offset, end int, more bool) []byte {
+	ip := make([]byte, 20, 20+end-offset)
+	ip[0] = 0x45
+	binary.BigEndian.PutUint16(ip[2:4], uint16(20+end-offset))
+	binary.BigEndian.PutUint16(ip[4:6], 0x1234)
+	flags := uint16(offset / 8)
+	if more {
+		flags |= 0x2000
+	}
+	binary.BigEndian.PutUint16(ip[6:8], flags)
+	ip[8] = 64
+	ip[9] = 17
+	copy(ip[12:16], []byte{10, 0, 0, 1})
+	copy(ip[16:20], []byte{239, 0, 0, 1})
+	return append(ip, udp[offset:end]...)
+}
+
+func TestReassemble(t *testing.T) {
+	payload := make([]byte, 40)
+	for i := range payload {
+		payload[i] = byte(i + 1)
+	}
+	udp := udpDatagram(payload) // 48 bytes: fragmentos de 16
+
+	type frag struct {
+		offset, end int
+		more        bool
+	}
+	tests := []struct {
+		name  string
+		frags []frag
+		want  bool // datagrama remontado no último fragmento
+	}{
+		{"em ordem", []frag{{0, 16, true}, {16, 32, true}, {32, 48, false}}, true},
+		{"fora de ordem", []frag{{32, 48, false}, {0, 16, true}, {16, 32, true}}, true},
+		{"duplicado", []frag{{0, 16, true}, {0, 16, true}, {32, 48, false}, {16, 32, true}}, true},
+		{"sobreposto", []frag{{0, 24, true}, {16, 40, true}, {32, 48, false}}, true},
+		{"faltando", []frag{{0, 16, true}, {32, 48, false}}, false},
+		// o duplicado não pode completar os 48 bytes com uma lacuna em 16..32
+		{"duplicado com lacuna", []frag{{0, 16, true}, {32, 48, false}, {32, 48, false}, {0, 16, true}}, false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			d := NewUDPDemux()
+			ts := time.Unix(1700000000, 0)
+			for i, f := range tt.frags {
+				dg, ok := d.parseIPv4(ts, ipv4Fragment(udp, f.offset, f.end, f.more))
+				if i < len(tt.frags)-1 {
+					if ok {
+						t.Fatalf("fragmento %d: datagrama antes de chegarem todos os fragmentos", i)
+					}
+					continue
+				}
+				if ok != tt.want {
+					t.Fatalf("remontado = %v, quer %v", ok, tt.want)
+				}
+				if !ok {
+					return
+				}
+				if !bytes.Equal(dg.Payload, payload) {
+					t.Errorf("payload = %v, quer %v", dg.Payload, payload)
+				}
+				if dg.SrcPort != 1000 || dg.DstPort != 8600 {
+					t.Errorf("portas = %d/%d", dg.SrcPort, dg.DstPort)
+				}
+				if len(d.frags) != 0 {
+					t.Errorf("%d remontagens pendentes", len(d.frags))
+				}
+			}
+		})
+	}
+}
+
+func TestReassembleTimeout(t *testing.T) {
+	udp := udpDatagram(make([]byte, 24))
+	d := NewUDPDemux()
+	ts := time.Unix(1700000000, 0)
+	d.parseIPv4(ts, ipv4Fragment(udp, 0, 16, true))
+	// o resto chega depois do tempo limite: a primeira metade foi descartada
+	ts = ts.Add(fragTimeout + time.Second)
+	if _, ok := d.parseIPv4(ts, ipv4Fragment(udp, 16, 32, false)); ok {
+		t.Fatal("remontou com um fragmento expirado")
+	}
+}
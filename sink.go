@@ -0,0 +1,286 @@
+package main
+
+import (
+	"fmt"
+	"slices"
+	"sort"
+	"strings"
+)
+
+//
+// ---------------- SAÍDAS ----------------
+//
+
+// Sink recebe as linhas convertidas de um datagroup. Os valores seguem os
+// tipos produzidos por Converter (nil = null). Close confirma a saída e
+// Abort a descarta quando a captura falha.
+type Sink interface {
+	WriteRow(values []any) error
+	Close() error
+	Abort()
+}
+
+// sinkTarget descreve onde um sink grava as linhas de um datagroup.
+type sinkTarget struct {
+	Path   string // <dir>/<modelo de nome>.<ext>
+	Stem   string // nome do arquivo de captura sem extensão
+	Name   string // datagroup
+	Source string // arquivo de captura
+	Fields []DataItem
+	Output OutputConfig
+}
+
+type sinkFormat struct {
+	ext  string
+	open func(t sinkTarget) (Sink, error)
+}
+
+// pathSink é implementado pelos sinks que gravam fora de t.Path, em
+// nenhum, um ou vários arquivos.
+type pathSink interface {
+	Paths() []string
+}
+
+var sinkFormats = map[string]sinkFormat{
+	"parquet": {".parquet", func(t sinkTarget) (Sink, error) {
+		w, err := newParquetWriter(t.Path, t.Fields, t.Output.Parquet)
+		if err != nil {
+			return nil, err
+		}
+		return w, nil
+	}},
+	"csv": {".csv", func(t sinkTarget) (Sink, error) {
+		w, err := newCSVWriter(t.Path, t.Fields, t.Output.CSV)
+		if err != nil {
+			return nil, err
+		}
+		return w, nil
+	}},
+	"ndjson": {".ndjson", func(t sinkTarget) (Sink, error) {
+		w, err := newNDJSONWriter(t.Path, t.Fields, t.Output.NDJSON)
+		if err != nil {
+			return nil, err
+		}
+		return w, nil
+	}},
+	"arrow": {".arrow", func(t sinkTarget) (Sink, error) {
+		w, err := newArrowWriter(t.Path, t.Fields, t.Output.Arrow)
+		if err != nil {
+			return nil, err
+		}
+		return w, nil
+	}},
+	"sqlite": {".sqlite", func(t sinkTarget) (Sink, error) {
+		w, err := newSQLiteWriter(t)
+		if err != nil {
+			return nil, err
+		}
+		return w, nil
+	}},
+	"dataset": {"", func(t sinkTarget) (Sink, error) {
+		w, err := newDatasetWriter(t)
+		if err != nil {
+			return nil, err
+		}
+		return w, nil
+	}},
+}
+
+// parseFormats interpreta -o: lista separada por vírgulas, sem repetições.
+func parseFormats(arg string) []string {
+	var formats []string
+	seen := map[string]bool{}
+	for _, f := range strings.Split(arg, ",") {
+		f = strings.ToLower(strings.TrimSpace(f))
+		if f == "feather" {
+			f = "arrow"
+		}
+		if f != "" && !seen[f] {
+			seen[f] = true
+			formats = append(formats, f)
+		}
+	}
+	return formats
+}
+
+func validFormats(formats []string) error {
+	for _, f := range formats {
+		if _, ok := sinkFormats[f]; !ok {
+			names := make([]string, 0, len(sinkFormats))
+			for name := range sinkFormats {
+				names = append(names, name)
+			}
+			sort.Strings(names)
+			return fmt.Errorf("formato desconhecido %q (use %s)", f, strings.Join(names, ", "))
+		}
+	}
+	return nil
+}
+
+// applyOutputFlags define os formatos de cada datagroup: -o substitui a
+// configuração, -csv acrescenta csv e, sem nada, a saída é parquet. -out
+// substitui o diretório das saídas.
+func applyOutputFlags(groups []*Group, formats []string, csv bool, dir string) {
+	for _, g := range groups {
+		if dir != "" {
+			g.Output.Dir = dir
+		}
+		g.Output = g.Output.underDir()
+		if len(formats) > 0 {
+			g.Output.Formats = formats
+		}
+		if len(g.Output.Formats) == 0 {
+			g.Output.Formats = []string{"parquet"}
+		}
+		if csv && !slices.Contains(g.Output.Formats, "csv") {
+			g.Output.Formats = append(slices.Clip(g.Output.Formats), "csv")
+		}
+	}
+}
+
+// merge aplica sobre o a configuração do datagroup.
+func (o OutputConfig) merge(over OutputConfig) OutputConfig {
+	if over.Formats != nil {
+		o.Formats = over.Formats
+	}
+	o.Parquet = o.Parquet.merge(over.Parquet)
+	o.CSV = o.CSV.merge(over.CSV)
+	o.NDJSON = o.NDJSON.merge(over.NDJSON)
+	o.Arrow = o.Arrow.merge(over.Arrow)
+	o.SQLite = o.SQLite.merge(over.SQLite)
+	o.Dataset = o.Dataset.merge(over.Dataset)
+	if over.Dir != "" {
+		o.Dir = over.Dir
+	}
+	if over.Name != "" {
+		o.Name = over.Name
+	}
+	if over.Exists != "" {
+		o.Exists = over.Exists
+	}
+	return o
+}
+
+func (o OutputConfig) validate() error {
+	if err := validFormats(o.Formats); err != nil {
+		return err
+	}
+	if err := o.Parquet.validate(); err != nil {
+		return fmt.Errorf("parquet: %w", err)
+	}
+	if err := o.CSV.validate(); err != nil {
+		return fmt.Errorf("csv: %w", err)
+	}
+	if err := o.Arrow.validate(); err != nil {
+		return fmt.Errorf("arrow: %w", err)
+	}
+	if err := validNameTemplate(o.Name); err != nil {
+		return err
+	}
+	if !validExists(o.Exists) {
+		return fmt.Errorf("exists inválido %q (use overwrite, skip ou fail)", o.Exists)
+	}
+	return nil
+}
+
+// groupOutput são as saídas de um datagroup para um arquivo de captura.
+type groupOutput struct {
+	group   *Group
+	conv    *Converter
+	sinks   []Sink
+	paths   []string
+	skipped []string // saídas que já existiam, com exists = skip
+	rows    int      // linhas gravadas
+}
+
+// openOutputs abre um sink por formato do datagroup, no caminho dado pelo
+// modelo de nome. O banco da campanha e o dataset têm caminho próprio.
+func openOutputs(g *Group, job Job, stem string, conv *Converter) (*groupOutput, error) {
+	o := &groupOutput{group: g, conv: conv}
+	vars := nameVars{Dir: job.Dir, Stem: stem, Datagroup: g.Name}
+	for _, format := range g.Output.Formats {
+		sf := sinkFormats[format]
+		t := sinkTarget{
+			Stem:   stem,
+			Name:   g.Name,
+			Source: job.File,
+			Fields: conv.Columns(),
+			Output: g.Output,
+		}
+
+		var s Sink
+		var err error
+		switch {
+		case ownPath(format, g.Output):
+			s, err = sf.open(t)
+		case rowDependent(g.Output.nameTemplate()):
+			s = newRouteSink(sf, t, vars)
+		default:
+			t.Path = g.Output.outputPath(vars, sf.ext)
+			s, err = openNamed(sf, t)
+		}
+		if err != nil {
+			o.Abort()
+			return nil, fmt.Errorf("%s: %w", format, err)
+		}
+		if s == nil {
+			o.skipped = append(o.skipped, t.Path)
+			continue
+		}
+		o.sinks = append(o.sinks, s)
+		o.paths = append(o.paths, t.Path)
+	}
+	return o, nil
+}
+
+// ownPath diz se o formato ignora o modelo de nome: o dataset e o banco da
+// campanha.
+func ownPath(format string, out OutputConfig) bool {
+	return format == "dataset" || (format == "sqlite" && out.SQLite.Path != "")
+}
+
+// files são os caminhos gravados, um por arquivo, para a mensagem de
+// conclusão e o manifesto.
+func (o *groupOutput) files() []string {
+	var files []string
+	for i, s := range o.sinks {
+		paths := []string{o.paths[i]}
+		if ps, ok := s.(pathSink); ok {
+			paths = ps.Paths()
+		}
+		for _, p := range paths {
+			if p != "" {
+				files = append(files, p)
+			}
+		}
+	}
+	return files
+}
+
+func (o *groupOutput) WriteRow(row []any) error {
+	for _, s := range o.sinks {
+		if err := s.WriteRow(row); err != nil {
+			return err
+		}
+	}
+	o.rows++
+	return nil
+}
+
+// Abort descarta as saídas de todos os sinks.
+func (o *groupOutput) Abort() {
+	for _, s := range o.sinks {
+		s.Abort()
+	}
+}
+
+// Close fecha todos os sinks e retorna o primeiro erro.
+func (o *groupOutput) Close() error {
+	var first error
+	for _, s := range o.sinks {
+		if err := s.Close(); err != nil && first == nil {
+			first = err
+		}
+	}
+	return first
+}
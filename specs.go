@@ -0,0 +1,484 @@
+package main
+
+import (
+	"encoding/json"
+	"encoding/xml"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strconv"
+	"strings"
+)
+
+//
+// ---------------- ESPECIFICAÇÕES ----------------
+//
+
+// SpecSet guarda todas as edições conhecidas de cada categoria.
+type SpecSet struct {
+	cats map[int]map[string]*Category
+}
+
+func NewSpecSet() *SpecSet {
+	return &SpecSet{cats: map[int]map[string]*Category{}}
+}
+
+// loadSpecs junta as UAPs embutidas com as do diretório decoder.specs.
+func loadSpecs(cfg Config) (*SpecSet, error) {
+	s := NewSpecSet()
+	for _, c := range builtinCategories() {
+		s.Add(c)
+	}
+	if cfg.Decoder.Specs != "" {
+		if err := s.LoadDir(cfg.Decoder.Specs); err != nil {
+			return nil, err
+		}
+	}
+	return s, nil
+}
+
+func (s *SpecSet) Add(c *Category) {
+	if s.cats[c.Number] == nil {
+		s.cats[c.Number] = map[string]*Category{}
+	}
+	s.cats[c.Number][c.Edition] = c
+}
+
+// LoadDir carrega todos os arquivos .xml e .json do diretório. Uma edição
+// carregada do disco substitui a embutida de mesmo número.
+func (s *SpecSet) LoadDir(dir string) error {
+	entries, err := os.ReadDir(dir)
+	if err != nil {
+		return err
+	}
+	for _, e := range entries {
+		if e.IsDir() {
+			continue
+		}
+		path := filepath.Join(dir, e.Name())
+
+		var c *Category
+		switch strings.ToLower(filepath.Ext(e.Name())) {
+		case ".xml":
+			c, err = loadXMLSpec(path)
+		case ".json":
+			c, err = loadJSONSpec(path)
+		default:
+			continue
+		}
+		if err != nil {
+			return fmt.Errorf("%s: %w", e.Name(), err)
+		}
+		s.Add(c)
+	}
+	return nil
+}
+
+// Editions lista as edições disponíveis de uma categoria, da mais antiga à mais nova.
+func (s *SpecSet) Editions(cat int) []string {
+	var eds []string
+	for ed := range s.cats[cat] {
+		eds = append(eds, ed)
+	}
+	sort.Slice(eds, func(i, j int) bool { return compareEditions(eds[i], eds[j]) < 0 })
+	return eds
+}
+
+// Resolve monta um decoder com a edição fixada para cada categoria em
+// pins ("021" = "2.4"); categorias sem pin usam a edição mais nova.
+func (s *SpecSet) Resolve(pins map[string]string) (*AsterixDecoder, error) {
+	chosen := map[int]string{}
+	for key, ed := range pins {
+		cat, err := strconv.Atoi(strings.TrimPrefix(strings.ToUpper(key), "CAT"))
+		if err != nil {
+			return nil, fmt.Errorf("categoria inválida %q", key)
+		}
+		if s.cats[cat][ed] == nil {
+			return nil, fmt.Errorf("CAT%03d edição %s não encontrada (disponíveis: %s)",
+				cat, ed, strings.Join(s.Editions(cat), ", "))
+		}
+		chosen[cat] = ed
+	}
+
+	var cats []*Category
+	for cat := range s.cats {
+		ed, ok := chosen[cat]
+		if !ok {
+			eds := s.Editions(cat)
+			ed = eds[len(eds)-1]
+		}
+		cats = append(cats, s.cats[cat][ed])
+	}
+	return NewAsterixDecoder(cats...), nil
+}
+
+// compareEditions compara edições numericamente por componente ("1.21" > "1.3").
+func compareEditions(a, b string) int {
+	pa, pb := strings.Split(a, "."), strings.Split(b, ".")
+	for i := 0; i < len(pa) || i < len(pb); i++ {
+		var x, y int
+		if i < len(pa) {
+			x, _ = strconv.Atoi(pa[i])
+		}
+		if i < len(pb) {
+			y, _ = strconv.Atoi(pb[i])
+		}
+		if x != y {
+			if x < y {
+				return -1
+			}
+			return 1
+		}
+	}
+	return strings.Compare(a, b)
+}
+
+//
+// JSON: o mesmo modelo de Category/Item/Element
+//
+
+type jsonSpec struct {
+	Category int      `json:"category"`
+	Edition  string   `json:"edition"`
+	UAP      []string `json:"uap"`
+	Items    []*Item  `json:"items"`
+}
+
+func loadJSONSpec(path string) (*Category, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, err
+	}
+	var spec jsonSpec
+	if err := json.Unmarshal(data, &spec); err != nil {
+		return nil, err
+	}
+	if spec.Category == 0 || spec.Edition == "" {
+		return nil, fmt.Errorf("category e edition são obrigatórios")
+	}
+	return newCategory(spec.Category, spec.Edition, spec.UAP, spec.Items...), nil
+}
+
+//
+// XML: formato <Category id="21" ver="2.4"> com <DataItem> e <UAP>
+//
+
+type xmlNode struct {
+	XMLName  xml.Name
+	Attrs    []xml.Attr `xml:",any,attr"`
+	Text     string     `xml:",chardata"`
+	Children []xmlNode  `xml:",any"`
+}
+
+func (n *xmlNode) attr(name string) string {
+	for _, a := range n.Attrs {
+		if a.Name.Local == name {
+			return a.Value
+		}
+	}
+	return ""
+}
+
+func (n *xmlNode) child(name string) *xmlNode {
+	for i := range n.Children {
+		if n.Children[i].XMLName.Local == name {
+			return &n.Children[i]
+		}
+	}
+	return nil
+}
+
+func (n *xmlNode) childText(name string) string {
+	if c := n.child(name); c != nil {
+		return strings.TrimSpace(c.Text)
+	}
+	return ""
+}
+
+func loadXMLSpec(path string) (*Category, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, err
+	}
+	var root xmlNode
+	if err := xml.Unmarshal(data, &root); err != nil {
+		return nil, err
+	}
+	if root.XMLName.Local != "Category" {
+		return nil, fmt.Errorf("elemento raiz <%s>, esperado <Category>", root.XMLName.Local)
+	}
+	cat, err := strconv.Atoi(root.attr("id"))
+	if err != nil {
+		return nil, fmt.Errorf("id de categoria inválido %q", root.attr("id"))
+	}
+	edition := root.attr("ver")
+
+	var items []*Item
+	var uap []string
+	for i := range root.Children {
+		n := &root.Children[i]
+		switch n.XMLName.Local {
+		case "DataItem":
+			format := n.child("DataItemFormat")
+			if format == nil || len(format.Children) == 0 {
+				return nil, fmt.Errorf("item %s sem DataItemFormat", n.attr("id"))
+			}
+			it, err := xmlItem(&format.Children[0], n.attr("id"), n.childText("DataItemName"))
+			if err != nil {
+				return nil, fmt.Errorf("item %s: %w", n.attr("id"), err)
+			}
+			items = append(items, it)
+
+		case "UAP":
+			if uap != nil {
+				continue // apenas a primeira UAP
+			}
+			uap = []string{}
+			for j := range n.Children {
+				u := &n.Children[j]
+				frn, err := strconv.Atoi(u.attr("frn"))
+				if err != nil {
+					continue // FX
+				}
+				for len(uap) < frn {
+					uap = append(uap, "")
+				}
+				if id := strings.TrimSpace(u.Text); id != "-" {
+					uap[frn-1] = id
+				}
+			}
+		}
+	}
+	if uap == nil {
+		return nil, fmt.Errorf("CAT%03d sem UAP", cat)
+	}
+	return newCategory(cat, edition, uap, items...), nil
+}
+
+func xmlItem(n *xmlNode, id, name string) (*Item, error) {
+	it := &Item{ID: id, Name: name}
+
+	switch n.XMLName.Local {
+	case "Fixed":
+		it.Format = FormatFixed
+		it.Elements = valueName(xmlElements(n, false))
+
+	case "Variable":
+		it.Format = FormatExtended
+		for i := range n.Children {
+			if n.Children[i].XMLName.Local == "Fixed" {
+				it.Groups = append(it.Groups, xmlElements(&n.Children[i], true))
+			}
+		}
+
+	case "Repetitive":
+		it.Format = FormatRepetitive
+		inner := firstFormat(n)
+		if inner == nil {
+			return nil, fmt.Errorf("repetitive vazio")
+		}
+		sub, err := xmlItem(inner, id, name)
+		if err != nil {
+			return nil, err
+		}
+		it.Elements = sub.Elements
+
+	case "Explicit":
+		it.Format = FormatExplicit
+		if inner := firstFormat(n); inner != nil {
+			sub, err := xmlItem(inner, id, name)
+			if err != nil {
+				return nil, err
+			}
+			it.Elements = sub.Elements
+		}
+
+	case "BDS":
+		it.Format = FormatFixed
+		it.Elements = []Element{rawEl("MBDATA", 56), rawEl("BDS1", 4), rawEl("BDS2", 4)}
+
+	case "Compound":
+		it.Format = FormatCompound
+		var formats []*xmlNode
+		for i := range n.Children {
+			if n.Children[i].XMLName.Local != "" {
+				formats = append(formats, &n.Children[i])
+			}
+		}
+		if len(formats) == 0 || formats[0].XMLName.Local != "Variable" {
+			return nil, fmt.Errorf("compound sem subcampo primário")
+		}
+		subs, err := xmlSubitems(formats[0], formats[1:])
+		if err != nil {
+			return nil, err
+		}
+		it.Subitems = subs
+
+	default:
+		return nil, fmt.Errorf("formato <%s> não suportado", n.XMLName.Local)
+	}
+	return it, nil
+}
+
+// xmlSubitems associa os bits do subcampo primário aos formatos seguintes.
+func xmlSubitems(primary *xmlNode, formats []*xmlNode) ([]*Item, error) {
+	var subs []*Item
+	next := 0
+	for i := range primary.Children {
+		fx := &primary.Children[i]
+		if fx.XMLName.Local != "Fixed" {
+			continue
+		}
+		slots := make([]*Item, 7)
+		for j := range fx.Children {
+			b := &fx.Children[j]
+			if b.XMLName.Local != "Bits" || b.attr("fx") == "1" {
+				continue
+			}
+			bit, err := strconv.Atoi(b.attr("bit"))
+			if err != nil || bit < 2 || bit > 8 {
+				continue
+			}
+			short := specName(b.childText("BitsShortName"))
+			if short == "" || short == "SPARE" {
+				continue
+			}
+			idx := next
+			if p := b.childText("BitsPresence"); p != "" {
+				if n, err := strconv.Atoi(p); err == nil {
+					idx = n - 1
+				}
+			}
+			next = idx + 1
+			if idx < 0 || idx >= len(formats) {
+				return nil, fmt.Errorf("subcampo %s sem formato", short)
+			}
+			sub, err := xmlItem(formats[idx], short, b.childText("BitsName"))
+			if err != nil {
+				return nil, fmt.Errorf("subcampo %s: %w", short, err)
+			}
+			slots[8-bit] = sub
+		}
+		subs = append(subs, slots...)
+	}
+	if len(subs) == 0 {
+		return nil, fmt.Errorf("subcampo primário vazio")
+	}
+	return subs, nil
+}
+
+func firstFormat(n *xmlNode) *xmlNode {
+	for i := range n.Children {
+		switch n.Children[i].XMLName.Local {
+		case "Fixed", "Variable", "Repetitive", "Compound", "Explicit", "BDS":
+			return &n.Children[i]
+		}
+	}
+	return nil
+}
+
+// xmlElements converte os <Bits> de um <Fixed> em elementos ordenados do
+// bit mais significativo ao menos significativo, preenchendo lacunas com
+// spare. Com fx, o bit 1 é o FX do grupo e fica de fora.
+func xmlElements(n *xmlNode, fx bool) []Element {
+	length, _ := strconv.Atoi(n.attr("length"))
+	end := 0
+	if fx {
+		end = 1
+	}
+	type bitsDef struct {
+		hi, lo int
+		el     Element
+	}
+	var defs []bitsDef
+	for i := range n.Children {
+		b := &n.Children[i]
+		if b.XMLName.Local != "Bits" || b.attr("fx") == "1" {
+			continue
+		}
+		var hi, lo int
+		if v := b.attr("bit"); v != "" {
+			hi, _ = strconv.Atoi(v)
+			lo = hi
+		} else {
+			hi, _ = strconv.Atoi(b.attr("from"))
+			lo, _ = strconv.Atoi(b.attr("to"))
+		}
+		if hi < lo {
+			hi, lo = lo, hi
+		}
+		if lo <= end || hi > length*8 {
+			continue
+		}
+
+		el := Element{Name: specName(b.childText("BitsShortName")), Bits: hi - lo + 1, Kind: KindRaw}
+		switch b.attr("encode") {
+		case "signed":
+			el.Kind = KindSigned
+		case "6bitschar":
+			el.Kind = KindICAO6
+		case "octal":
+			el.Kind = KindOctal
+		case "ascii":
+			el.Kind = KindASCII
+		case "hex":
+			el.Kind = KindHex
+		}
+		if u := b.child("BitsUnit"); u != nil {
+			el.LSB, _ = strconv.ParseFloat(u.attr("scale"), 64)
+			el.Unit = strings.TrimSpace(u.Text)
+		}
+		if el.Name == "" || el.Name == "SPARE" {
+			el = spareEl(el.Bits)
+		}
+		defs = append(defs, bitsDef{hi: hi, lo: lo, el: el})
+	}
+	sort.SliceStable(defs, func(i, j int) bool { return defs[i].hi > defs[j].hi })
+
+	var elems []Element
+	pos := length * 8
+	for _, d := range defs {
+		if d.hi > pos {
+			continue // definição alternativa sobreposta
+		}
+		if d.hi < pos {
+			elems = append(elems, spareEl(pos-d.hi))
+		}
+		elems = append(elems, d.el)
+		pos = d.lo - 1
+	}
+	if pos > end {
+		elems = append(elems, spareEl(pos-end))
+	}
+	return elems
+}
+
+// valueName usa VALUE quando o item tem um único elemento, como o wireshark.
+func valueName(elems []Element) []Element {
+	idx := -1
+	for i, e := range elems {
+		if e.Kind == KindSpare {
+			continue
+		}
+		if idx >= 0 {
+			return elems
+		}
+		idx = i
+	}
+	if idx >= 0 {
+		elems[idx].Name = "VALUE"
+	}
+	return elems
+}
+
+// specName normaliza nomes curtos para o padrão dos campos (maiúsculas, sem pontuação).
+func specName(s string) string {
+	var sb strings.Builder
+	for _, r := range strings.ToUpper(s) {
+		if (r >= 'A' && r <= 'Z') || (r >= '0' && r <= '9') || r == '_' {
+			sb.WriteRune(r)
+		}
+	}
+	return sb.String()
+}
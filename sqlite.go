@@ -0,0 +1,440 @@
+package main
+
+import (
+	"database/sql"
+	"fmt"
+	"math"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"sync"
+
+	_ "modernc.org/sqlite"
+)
+
+//
+// ---------------- SQLITE ----------------
+//
+
+type SQLiteOptions struct {
+	Path    string   `toml:"path"`    // banco único da campanha; vazio = um .sqlite por arquivo
+	Batch   int      `toml:"batch"`   // linhas por transação (padrão 10000)
+	Indexes []string `toml:"indexes"` // labels indexados; padrão endereço do alvo e tempo
+}
+
+func (o SQLiteOptions) merge(over SQLiteOptions) SQLiteOptions {
+	if over.Path != "" {
+		o.Path = over.Path
+	}
+	if over.Batch != 0 {
+		o.Batch = over.Batch
+	}
+	if over.Indexes != nil {
+		o.Indexes = over.Indexes
+	}
+	return o
+}
+
+// campos indexados quando indexes não é configurado
+var sqliteDefaultIndexes = map[string]bool{
+	"frame.time_epoch":      true,
+	"asterix.021_080_VALUE": true, // target address
+	"asterix.048_220_VALUE": true, // aircraft address
+}
+
+// sqliteDB é um banco aberto. O banco da campanha é compartilhado pelos
+// workers, que gravam um lote por vez sob o mutex.
+type sqliteDB struct {
+	mu      sync.Mutex
+	db      *sql.DB
+	indexes map[string][]string // tabela -> colunas, criados no fechamento
+}
+
+var sqliteDBs = struct {
+	sync.Mutex
+	m map[string]*sqliteDB
+}{m: map[string]*sqliteDB{}}
+
+func openSQLiteDB(path string) (*sqliteDB, error) {
+	db, err := sql.Open("sqlite", path)
+	if err != nil {
+		return nil, err
+	}
+	db.SetMaxOpenConns(1)
+	return &sqliteDB{db: db, indexes: map[string][]string{}}, nil
+}
+
+// campaignDB retorna o banco compartilhado de path, abrindo na primeira vez.
+func campaignDB(path string) (*sqliteDB, error) {
+	sqliteDBs.Lock()
+	defer sqliteDBs.Unlock()
+	if d, ok := sqliteDBs.m[path]; ok {
+		return d, nil
+	}
+	if err := os.MkdirAll(filepath.Dir(path), 0o755); err != nil {
+		return nil, err
+	}
+	d, err := openSQLiteDB(path)
+	if err != nil {
+		return nil, err
+	}
+	sqliteDBs.m[path] = d
+	return d, nil
+}
+
+// closeSQLiteDBs cria os índices e fecha os bancos de campanha. É chamada
+// depois que todos os arquivos foram processados.
+func closeSQLiteDBs() error {
+	sqliteDBs.Lock()
+	defer sqliteDBs.Unlock()
+	var first error
+	for path, d := range sqliteDBs.m {
+		if err := d.close(); err != nil && first == nil {
+			first = fmt.Errorf("%s: %w", path, err)
+		}
+		delete(sqliteDBs.m, path)
+	}
+	return first
+}
+
+type sqliteColumn struct {
+	name string
+	typ  string
+}
+
+func columnDefs(cols []sqliteColumn) string {
+	defs := make([]string, len(cols))
+	for i, c := range cols {
+		defs[i] = sqlIdent(c.name) + " " + c.typ
+	}
+	return strings.Join(defs, ", ")
+}
+
+// createTable cria a tabela ou, se ela já existe, confere se as colunas são
+// as configuradas, na mesma ordem e com a mesma afinidade.
+func (d *sqliteDB) createTable(table string, cols []sqliteColumn, indexes []string) error {
+	d.mu.Lock()
+	defer d.mu.Unlock()
+	if _, err := d.db.Exec(fmt.Sprintf("CREATE TABLE IF NOT EXISTS %s (%s)", sqlIdent(table), columnDefs(cols))); err != nil {
+		return err
+	}
+	existing, err := d.columns(table)
+	if err != nil {
+		return err
+	}
+	if len(existing) != len(cols) {
+		return fmt.Errorf("tabela %s tem %d colunas no banco e %d na configuração", table, len(existing), len(cols))
+	}
+	for i, c := range cols {
+		if e := existing[i]; e.name != c.name || !strings.EqualFold(e.typ, c.typ) {
+			return fmt.Errorf("tabela %s: coluna %d é %s %s no banco e %s %s na configuração", table, i+1, e.name, e.typ, c.name, c.typ)
+		}
+	}
+	d.indexes[table] = indexes
+	return nil
+}
+
+func (d *sqliteDB) columns(table string) ([]sqliteColumn, error) {
+	rows, err := d.db.Query("SELECT name, type FROM pragma_table_info(?) ORDER BY cid", table)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	var cols []sqliteColumn
+	for rows.Next() {
+		var c sqliteColumn
+		if err := rows.Scan(&c.name, &c.typ); err != nil {
+			return nil, err
+		}
+		cols = append(cols, c)
+	}
+	return cols, rows.Err()
+}
+
+func (d *sqliteDB) exec(query string, args ...any) error {
+	d.mu.Lock()
+	defer d.mu.Unlock()
+	_, err := d.db.Exec(query, args...)
+	return err
+}
+
+// insert grava as linhas numa única transação.
+func (d *sqliteDB) insert(query string, rows [][]any) error {
+	d.mu.Lock()
+	defer d.mu.Unlock()
+
+	tx, err := d.db.Begin()
+	if err != nil {
+		return err
+	}
+	stmt, err := tx.Prepare(query)
+	if err != nil {
+		tx.Rollback()
+		return err
+	}
+	defer stmt.Close()
+
+	for _, row := range rows {
+		if _, err := stmt.Exec(row...); err != nil {
+			tx.Rollback()
+			return err
+		}
+	}
+	return tx.Commit()
+}
+
+// replaceSource troca as linhas da captura pelas da tabela parcial numa
+// única transação: uma queda no meio deixa o banco como estava.
+func (d *sqliteDB) replaceSource(table, stage, source string) error {
+	d.mu.Lock()
+	defer d.mu.Unlock()
+
+	tx, err := d.db.Begin()
+	if err != nil {
+		return err
+	}
+	_, err = tx.Exec("DELETE FROM "+table+" WHERE source_file = ?", source)
+	if err == nil {
+		_, err = tx.Exec("INSERT INTO " + table + " SELECT * FROM " + stage)
+	}
+	if err == nil {
+		_, err = tx.Exec("DROP TABLE " + stage)
+	}
+	if err != nil {
+		tx.Rollback()
+		return err
+	}
+	return tx.Commit()
+}
+
+func (d *sqliteDB) close() error {
+	var first error
+	for table, cols := range d.indexes {
+		for _, c := range cols {
+			q := fmt.Sprintf("CREATE INDEX IF NOT EXISTS %s ON %s (%s)",
+				sqlIdent("idx_"+table+"_"+c), sqlIdent(table), sqlIdent(c))
+			if _, err := d.db.Exec(q); err != nil && first == nil {
+				first = err
+			}
+		}
+	}
+	if err := d.db.Close(); err != nil && first == nil {
+		first = err
+	}
+	return first
+}
+
+// SQLiteWriter grava as linhas numa tabela com o nome do datagroup. Com
+// path configurado, todos os arquivos vão para o mesmo banco, com a
+// coluna source_file; as linhas passam por uma tabela parcial e substituem
+// as da captura só no fechamento.
+type SQLiteWriter struct {
+	db     *sqliteDB
+	path   string
+	tmp    string // banco por arquivo: <path>.partial até o fechamento
+	shared bool
+	source string
+	table  string
+	stage  string // banco da campanha: <datagroup>.partial.<captura>
+	insert string
+	rows   [][]any
+	batch  int
+}
+
+func newSQLiteWriter(t sinkTarget) (*SQLiteWriter, error) {
+	opts := t.Output.SQLite
+	w := &SQLiteWriter{path: t.Path, batch: opts.Batch}
+	if w.batch <= 0 {
+		w.batch = 10000
+	}
+
+	var err error
+	if opts.Path != "" {
+		w.path, w.shared, w.source = opts.Path, true, t.Source
+		w.db, err = campaignDB(opts.Path)
+	} else {
+		// o banco por arquivo é recriado, como as outras saídas
+		w.tmp = t.Path + partialSuffix
+		if err := os.Remove(w.tmp); err != nil && !os.IsNotExist(err) {
+			return nil, err
+		}
+		w.db, err = openSQLiteDB(w.tmp)
+	}
+	if err != nil {
+		return nil, err
+	}
+	if !w.shared {
+		trackPartial(w.tmp, func() {
+			w.db.db.Close()
+			os.Remove(w.tmp)
+		})
+	}
+
+	cols := make([]sqliteColumn, 0, len(t.Fields)+1)
+	names := make([]string, 0, len(t.Fields)+1)
+	for _, f := range t.Fields {
+		cols = append(cols, sqliteColumn{f.Label, sqliteType(f.Type)})
+		names = append(names, sqlIdent(f.Label))
+	}
+	indexes := sqliteIndexes(t.Fields, opts.Indexes)
+	if w.shared {
+		cols = append(cols, sqliteColumn{"source_file", "TEXT"})
+		names = append(names, "source_file")
+		indexes = append(indexes, "source_file")
+	}
+
+	if err := w.db.createTable(t.Name, cols, indexes); err != nil {
+		w.Abort()
+		return nil, fmt.Errorf("%s: %w", w.path, err)
+	}
+	w.table = sqlIdent(t.Name)
+	target := w.table
+	if w.shared {
+		// uma tabela parcial deixada por uma execução interrompida é refeita
+		stage := t.Name + partialSuffix + "." + sourceID(t.Source)
+		if err := w.db.exec("DROP TABLE IF EXISTS " + sqlIdent(stage)); err != nil {
+			return nil, err
+		}
+		if err := w.db.exec(fmt.Sprintf("CREATE TABLE %s (%s)", sqlIdent(stage), columnDefs(cols))); err != nil {
+			return nil, err
+		}
+		w.stage, target = stage, sqlIdent(stage)
+		trackPartial(w.stageKey(), w.dropStage)
+	}
+
+	marks := strings.TrimSuffix(strings.Repeat("?, ", len(names)), ", ")
+	w.insert = fmt.Sprintf("INSERT INTO %s (%s) VALUES (%s)", target, strings.Join(names, ", "), marks)
+	return w, nil
+}
+
+// sqliteIndexes retorna os labels configurados ou, sem configuração, os
+// campos de endereço do alvo e de tempo.
+func sqliteIndexes(fields []DataItem, configured []string) []string {
+	if configured != nil {
+		return append([]string{}, configured...)
+	}
+	var labels []string
+	for _, f := range fields {
+		if sqliteDefaultIndexes[f.Field] {
+			labels = append(labels, f.Label)
+		}
+	}
+	return labels
+}
+
+func (w *SQLiteWriter) Paths() []string {
+	return []string{w.path}
+}
+
+func (w *SQLiteWriter) WriteRow(values []any) error {
+	row := make([]any, len(values), len(values)+1)
+	for i, v := range values {
+		row[i] = sqliteValue(v)
+	}
+	if w.shared {
+		row = append(row, w.source)
+	}
+	w.rows = append(w.rows, row)
+	if len(w.rows) >= w.batch {
+		return w.flush()
+	}
+	return nil
+}
+
+func (w *SQLiteWriter) flush() error {
+	err := w.db.insert(w.insert, w.rows)
+	w.rows = w.rows[:0]
+	return err
+}
+
+func (w *SQLiteWriter) Close() error {
+	var err error
+	if len(w.rows) > 0 {
+		err = w.flush()
+	}
+	if w.shared {
+		// o banco da campanha fica aberto até o fim
+		if err == nil {
+			err = w.db.replaceSource(w.table, sqlIdent(w.stage), w.source)
+		}
+		if err != nil {
+			w.dropStage()
+		}
+		untrackPartial(w.stageKey())
+		return err
+	}
+	if cerr := w.db.close(); err == nil {
+		err = cerr
+	}
+	untrackPartial(w.tmp)
+	if err == nil {
+		err = os.Rename(w.tmp, w.path)
+	}
+	if err != nil {
+		os.Remove(w.tmp)
+	}
+	return err
+}
+
+// Abort descarta o banco por arquivo ou, no da campanha, a tabela parcial;
+// as linhas de uma conversão anterior da captura ficam como estavam.
+func (w *SQLiteWriter) Abort() {
+	w.rows = nil
+	if w.shared {
+		if w.stage != "" {
+			w.dropStage()
+			untrackPartial(w.stageKey())
+		}
+		return
+	}
+	w.db.db.Close()
+	os.Remove(w.tmp)
+	untrackPartial(w.tmp)
+}
+
+func (w *SQLiteWriter) dropStage() {
+	w.db.exec("DROP TABLE IF EXISTS " + sqlIdent(w.stage))
+}
+
+// stageKey identifica a tabela parcial no registro de arquivos parciais.
+func (w *SQLiteWriter) stageKey() string {
+	return w.path + "#" + w.stage
+}
+
+// sqliteType escolhe a afinidade da coluna a partir do type do campo.
+// Timestamps ficam em texto ISO 8601, que as funções de data do SQLite e
+// do DuckDB entendem.
+func sqliteType(t string) string {
+	switch t {
+	case "bool", "int8", "int16", "int32", "int64", "uint8", "uint16", "uint32", "uint64":
+		return "INTEGER"
+	case "float32", "float64":
+		return "REAL"
+	}
+	return "TEXT"
+}
+
+func sqliteValue(v any) any {
+	switch x := v.(type) {
+	case bool:
+		if x {
+			return int64(1)
+		}
+		return int64(0)
+	case uint64:
+		if x > math.MaxInt64 {
+			return strconv.FormatUint(x, 10)
+		}
+		return int64(x)
+	case float32:
+		return exprValue(x)
+	case string, int8, int16, int32, int64, uint8, uint16, uint32, float64, nil:
+		return v
+	}
+	return formatValue(v)
+}
+
+func sqlIdent(s string) string {
+	return `"` + strings.ReplaceAll(s, `"`, `""`) + `"`
+}
@@ -0,0 +1,79 @@
+package main
+
+import (
+	"fmt"
+	"strconv"
+)
+
+//
+// ---------------- HORA DO DIA ----------------
+//
+
+// modos de reconstrução da hora do dia (tod)
+const (
+	TODAbsolute = "absolute" // data da captura + hora do dia, em segundos desde a época
+	TODLatency  = "latency"  // hora do quadro - hora do dia, em segundos
+)
+
+const (
+	microsPerDay     = 86400 * 1_000_000
+	microsPerHalfDay = microsPerDay / 2
+)
+
+// resolveTOD combina a hora do dia ASTERIX (segundos desde a meia-noite
+// UTC, com volta em 24h) com a data de frame.time_epoch. O dia escolhido é
+// o que deixa o instante a menos de 12h do quadro, o que resolve a virada
+// da meia-noite nos dois sentidos. Como o epoch Unix, ignora segundos
+// bissextos.
+func resolveTOD(mode, tod, frameEpoch string) string {
+	if tod == "" || frameEpoch == "" {
+		return ""
+	}
+	ts, err := parseTimestamp(frameEpoch)
+	if err != nil {
+		return ""
+	}
+	frame := int64(ts)
+	secs, err := strconv.ParseFloat(tod, 64)
+	if err != nil || secs < 0 || secs >= 86400 {
+		return ""
+	}
+
+	day := frame - mod(frame, microsPerDay)
+	abs := day + int64(secs*1e6+0.5)
+	switch diff := abs - frame; {
+	case diff > microsPerHalfDay:
+		abs -= microsPerDay
+	case diff < -microsPerHalfDay:
+		abs += microsPerDay
+	}
+
+	if mode == TODLatency {
+		return formatMicros(frame - abs)
+	}
+	return formatMicros(abs)
+}
+
+func formatMicros(us int64) string {
+	sign := ""
+	if us < 0 {
+		sign, us = "-", -us
+	}
+	return fmt.Sprintf("%s%d.%06d", sign, us/1_000_000, us%1_000_000)
+}
+
+func mod(a, b int64) int64 {
+	m := a % b
+	if m < 0 {
+		m += b
+	}
+	return m
+}
+
+func validTOD(s string) bool {
+	switch s {
+	case "", TODAbsolute, TODLatency:
+		return true
+	}
+	return false
+}
@@ -0,0 +1,205 @@
+package main
+
+import (
+	"bufio"
+	"context"
+	"encoding/xml"
+	"fmt"
+	"io"
+	"os/exec"
+	"strconv"
+	"strings"
+	"time"
+)
+
+//
+// ---------------- TSHARK ----------------
+//
+
+// tsharkRecordField é o nó do PDML que delimita cada registro ASTERIX.
+const tsharkRecordField = "asterix.message"
+
+// opções que o pshark passa ao tshark; em tshark.parameters são ignoradas
+var tsharkOwnOptions = []string{"-r", "-T", "-E", "-J", "-Y"}
+
+// splitParameters separa de parameters as opções definidas pelo pshark,
+// com o seu valor (-T fields ou -Tfields). Configs antigas trazem
+// -T fields -E ... da época da saída em colunas.
+func (t TShark) splitParameters() (keep, ignored []string) {
+	for i := 0; i < len(t.Parameters); i++ {
+		p := t.Parameters[i]
+		own := ""
+		for _, opt := range tsharkOwnOptions {
+			if strings.HasPrefix(p, opt) {
+				own = opt
+			}
+		}
+		switch {
+		case own == "":
+			keep = append(keep, p)
+		case p == own && i+1 < len(t.Parameters):
+			ignored = append(ignored, p+" "+t.Parameters[i+1])
+			i++
+		default:
+			ignored = append(ignored, p)
+		}
+	}
+	return keep, ignored
+}
+
+// warnIgnored avisa das opções de parameters que o tshark não vai receber.
+func (t TShark) warnIgnored(warn func(format string, args ...any)) {
+	if _, ignored := t.splitParameters(); len(ignored) > 0 {
+		warn("tshark.parameters: %s ignorado, definido pelo pshark", strings.Join(ignored, ", "))
+	}
+}
+
+// tsharkArgs monta a linha de comando: entrada, parameters e saída PDML.
+func tsharkArgs(filename string, t TShark) []string {
+	keep, _ := t.splitParameters()
+	args := append([]string{"-r", filename}, keep...)
+	return append(args, "-T", "pdml", "-J", "frame ip udp asterix", "-Y", "asterix")
+}
+
+// readTshark lê o PDML do tshark, que mantém cada item dentro do seu
+// registro, ao contrário da saída -T fields.
+//
+// A captura é lida pelo pshark, descomprimida quando necessário, e entregue
+// ao tshark pela entrada padrão (-r -): ele não lê xz, e o manifesto registra
+// o hash dos bytes que o tshark realmente recebeu. Quando ctx termina
+// (Ctrl+C, decoder.timeout) o tshark é encerrado.
+func (a *App) readTshark(ctx context.Context, in *inputFile, emit func(Packet) error) error {
+	cmd := exec.CommandContext(ctx, a.cfg.Tshark.Path, tsharkArgs("-", a.cfg.Tshark)...)
+	// sem esperar indefinidamente pelos pipes depois de matar o processo
+	cmd.WaitDelay = 5 * time.Second
+	cmd.Stdin = in
+	stdout, err := cmd.StdoutPipe()
+	if err != nil {
+		return err
+	}
+	stderr, err := cmd.StderrPipe()
+	if err != nil {
+		return err
+	}
+
+	go func() {
+		scanner := bufio.NewScanner(stderr)
+		for scanner.Scan() {
+			fmt.Println("[tshark]", scanner.Text())
+		}
+	}()
+
+	if err := cmd.Start(); err != nil {
+		return fmt.Errorf("falha ao iniciar tshark: %w", err)
+	}
+
+	if err := parsePDML(stdout, emit); err != nil {
+		_ = cmd.Process.Kill()
+		_ = cmd.Wait()
+		if ctx.Err() != nil {
+			return ctx.Err()
+		}
+		return err
+	}
+
+	if err := cmd.Wait(); err != nil {
+		if ctx.Err() != nil {
+			return ctx.Err()
+		}
+		return fmt.Errorf("erro tshark: %w", err)
+	}
+	return nil
+}
+
+// parsePDML percorre o XML em streaming e emite um Packet por <packet>.
+// Campos dentro de asterix.message vão para o registro; campos fora do
+// protocolo asterix são campos de quadro.
+func parsePDML(r io.Reader, emit func(Packet) error) error {
+	dec := xml.NewDecoder(bufio.NewReaderSize(r, 1<<20))
+
+	var (
+		pkt       Packet
+		rec       *Record
+		category  int
+		inAsterix bool
+		depth     int // profundidade de <field> dentro do protocolo atual
+		recDepth  int // profundidade do asterix.message aberto
+	)
+
+	for {
+		tok, err := dec.Token()
+		if err == io.EOF {
+			return nil
+		}
+		if err != nil {
+			return fmt.Errorf("pdml: %w", err)
+		}
+
+		switch t := tok.(type) {
+		case xml.StartElement:
+			switch t.Name.Local {
+			case "packet":
+				pkt = Packet{Fields: map[string]string{}}
+
+			case "proto":
+				inAsterix = pdmlAttr(t, "name") == "asterix"
+				depth = 0
+
+			case "field":
+				depth++
+				name, show := pdmlAttr(t, "name"), pdmlAttr(t, "show")
+				if !inAsterix {
+					if _, ok := pkt.Fields[name]; !ok && name != "" {
+						pkt.Fields[name] = show
+					}
+					continue
+				}
+				switch {
+				case name == "asterix.category":
+					category, _ = strconv.Atoi(show)
+				case name == tsharkRecordField && rec == nil:
+					rec = &Record{Category: category, Fields: map[string]string{}}
+					recDepth = depth
+				case rec != nil && name != "" && pdmlHasAttr(t, "show"):
+					appendField(rec.Fields, name, show)
+				}
+			}
+
+		case xml.EndElement:
+			switch t.Name.Local {
+			case "field":
+				if rec != nil && depth == recDepth {
+					pkt.Records = append(pkt.Records, *rec)
+					rec = nil
+				}
+				depth--
+
+			case "proto":
+				inAsterix = false
+
+			case "packet":
+				if err := emit(pkt); err != nil {
+					return err
+				}
+			}
+		}
+	}
+}
+
+func pdmlAttr(t xml.StartElement, name string) string {
+	for _, a := range t.Attr {
+		if a.Name.Local == name {
+			return a.Value
+		}
+	}
+	return ""
+}
+
+func pdmlHasAttr(t xml.StartElement, name string) bool {
+	for _, a := range t.Attr {
+		if a.Name.Local == name {
+			return true
+		}
+	}
+	return false
+}
@@ -0,0 +1,275 @@
+package main
+
+//
+// ---------------- UAP EMBUTIDAS ----------------
+//
+
+// builtinCategories retorna as UAPs compiladas no binário.
+func builtinCategories() []*Category {
+	return []*Category{cat021(), cat048()}
+}
+
+func rawEl(name string, bits int) Element {
+	return Element{Name: name, Bits: bits, Kind: KindRaw}
+}
+
+func uEl(name string, bits int, lsb float64, unit string) Element {
+	return Element{Name: name, Bits: bits, Kind: KindRaw, LSB: lsb, Unit: unit}
+}
+
+func hexEl(name string, bits int) Element {
+	return Element{Name: name, Bits: bits, Kind: KindHex}
+}
+
+func sEl(name string, bits int, lsb float64, unit string) Element {
+	return Element{Name: name, Bits: bits, Kind: KindSigned, LSB: lsb, Unit: unit}
+}
+
+func strEl(name string, bits int) Element {
+	return Element{Name: name, Bits: bits, Kind: KindICAO6}
+}
+
+func octEl(name string, bits int) Element {
+	return Element{Name: name, Bits: bits, Kind: KindOctal}
+}
+
+func spareEl(bits int) Element {
+	return Element{Bits: bits, Kind: KindSpare}
+}
+
+func grp(elems ...Element) []Element {
+	return elems
+}
+
+func fixedItem(id, name string, elems ...Element) *Item {
+	return &Item{ID: id, Name: name, Format: FormatFixed, Elements: elems}
+}
+
+func extItem(id, name string, groups ...[]Element) *Item {
+	return &Item{ID: id, Name: name, Format: FormatExtended, Groups: groups}
+}
+
+func repItem(id, name string, elems ...Element) *Item {
+	return &Item{ID: id, Name: name, Format: FormatRepetitive, Elements: elems}
+}
+
+func compItem(id, name string, subs ...*Item) *Item {
+	return &Item{ID: id, Name: name, Format: FormatCompound, Subitems: subs}
+}
+
+func explItem(id, name string) *Item {
+	return &Item{ID: id, Name: name, Format: FormatExplicit}
+}
+
+func newCategory(number int, edition string, uap []string, items ...*Item) *Category {
+	c := &Category{Number: number, Edition: edition, UAP: uap, Items: map[string]*Item{}}
+	for _, it := range items {
+		c.Items[it.ID] = it
+	}
+	return c
+}
+
+const (
+	lsbTime   = 1.0 / 128
+	lsbLat23  = 180.0 / (1 << 23)
+	lsbLat30  = 180.0 / (1 << 30)
+	lsbAngle  = 360.0 / (1 << 16)
+	lsbSpeed  = 1.0 / (1 << 14)
+	lsbTimeHP = 1.0 / (1 << 30)
+)
+
+// CAT021 ADS-B Target Reports, edição 2.4
+func cat021() *Category {
+	age := func(id, name string) *Item {
+		return fixedItem(id, name, uEl("VALUE", 8, 0.1, "s"))
+	}
+
+	return newCategory(21, "2.4",
+		[]string{
+			"010", "040", "161", "015", "071", "130", "131",
+			"072", "150", "151", "080", "073", "074", "075",
+			"076", "140", "090", "210", "070", "230", "145",
+			"152", "200", "155", "157", "160", "165", "077",
+			"170", "020", "220", "146", "148", "110", "016",
+			"008", "271", "132", "250", "260", "400", "295",
+			"", "", "", "", "", "RE", "SP",
+		},
+		fixedItem("010", "Data Source Identification", rawEl("SAC", 8), rawEl("SIC", 8)),
+		extItem("040", "Target Report Descriptor",
+			grp(rawEl("ATP", 3), rawEl("ARC", 2), rawEl("RC", 1), rawEl("RAB", 1)),
+			grp(rawEl("DCR", 1), rawEl("GBS", 1), rawEl("SIM", 1), rawEl("TST", 1), rawEl("SAA", 1), rawEl("CL", 2)),
+			grp(spareEl(1), rawEl("LLC", 1), rawEl("IPC", 1), rawEl("NOGO", 1), rawEl("CPR", 1), rawEl("LDPJ", 1), rawEl("RCF", 1)),
+			grp(rawEl("TBC_EP", 1), rawEl("TBC_VAL", 6)),
+			grp(rawEl("MBC_EP", 1), rawEl("MBC_VAL", 6)),
+		),
+		fixedItem("161", "Track Number", spareEl(4), rawEl("TRNUM", 12)),
+		fixedItem("015", "Service Identification", rawEl("VALUE", 8)),
+		fixedItem("071", "Time of Applicability for Position", uEl("VALUE", 24, lsbTime, "s")),
+		fixedItem("130", "Position in WGS-84 Co-ordinates",
+			sEl("LAT", 24, lsbLat23, "deg"), sEl("LON", 24, lsbLat23, "deg")),
+		fixedItem("131", "High-Resolution Position in WGS-84 Co-ordinates",
+			sEl("LAT", 32, lsbLat30, "deg"), sEl("LON", 32, lsbLat30, "deg")),
+		fixedItem("072", "Time of Applicability for Velocity", uEl("VALUE", 24, lsbTime, "s")),
+		fixedItem("150", "Air Speed", rawEl("IM", 1), rawEl("AS", 15)),
+		fixedItem("151", "True Airspeed", rawEl("RE", 1), uEl("TAS", 15, 1, "kt")),
+		fixedItem("080", "Target Address", hexEl("VALUE", 24)),
+		fixedItem("073", "Time of Message Reception for Position", uEl("VALUE", 24, lsbTime, "s")),
+		fixedItem("074", "Time of Message Reception of Position-High Precision",
+			rawEl("FSI", 2), uEl("TOMRP", 30, lsbTimeHP, "s")),
+		fixedItem("075", "Time of Message Reception for Velocity", uEl("VALUE", 24, lsbTime, "s")),
+		fixedItem("076", "Time of Message Reception of Velocity-High Precision",
+			rawEl("FSI", 2), uEl("TOMRV", 30, lsbTimeHP, "s")),
+		fixedItem("140", "Geometric Height", sEl("VALUE", 16, 6.25, "ft")),
+		extItem("090", "Quality Indicators",
+			grp(rawEl("NUCRNACV", 3), rawEl("NUCPNIC", 4)),
+			grp(rawEl("NICBARO", 1), rawEl("SIL", 2), rawEl("NACP", 4)),
+			grp(spareEl(2), rawEl("SILS", 1), rawEl("SDA", 2), rawEl("GVA", 2)),
+			grp(rawEl("PIC", 4), spareEl(3)),
+		),
+		fixedItem("210", "MOPS Version", spareEl(1), rawEl("VNS", 1), rawEl("VN", 3), rawEl("LTT", 3)),
+		fixedItem("070", "Mode 3/A Code", spareEl(4), octEl("MODE3A", 12)),
+		fixedItem("230", "Roll Angle", sEl("VALUE", 16, 0.01, "deg")),
+		fixedItem("145", "Flight Level", sEl("VALUE", 16, 0.25, "FL")),
+		fixedItem("152", "Magnetic Heading", uEl("VALUE", 16, lsbAngle, "deg")),
+		fixedItem("200", "Target Status",
+			rawEl("ICF", 1), rawEl("LNAV", 1), rawEl("ME", 1), rawEl("PS", 3), rawEl("SS", 2)),
+		fixedItem("155", "Barometric Vertical Rate", rawEl("RE", 1), sEl("BVR", 15, 6.25, "ft/min")),
+		fixedItem("157", "Geometric Vertical Rate", rawEl("RE", 1), sEl("GVR", 15, 6.25, "ft/min")),
+		fixedItem("160", "Airborne Ground Vector",
+			rawEl("RE", 1), uEl("GS", 15, lsbSpeed, "NM/s"), uEl("TA", 16, lsbAngle, "deg")),
+		fixedItem("165", "Track Angle Rate", spareEl(6), sEl("TAR", 10, 1.0/32, "deg/s")),
+		fixedItem("077", "Time of ASTERIX Report Transmission", uEl("VALUE", 24, lsbTime, "s")),
+		fixedItem("170", "Target Identification", strEl("VALUE", 48)),
+		fixedItem("020", "Emitter Category", rawEl("VALUE", 8)),
+		compItem("220", "Met Information",
+			fixedItem("WS", "Wind Speed", uEl("VALUE", 16, 1, "kt")),
+			fixedItem("WD", "Wind Direction", uEl("VALUE", 16, 1, "deg")),
+			fixedItem("TMP", "Temperature", sEl("VALUE", 16, 0.25, "degC")),
+			fixedItem("TRB", "Turbulence", rawEl("VALUE", 8)),
+		),
+		fixedItem("146", "Selected Altitude", rawEl("SAS", 1), rawEl("SRC", 2), sEl("ALT", 13, 25, "ft")),
+		fixedItem("148", "Final State Selected Altitude",
+			rawEl("MV", 1), rawEl("AH", 1), rawEl("AM", 1), sEl("ALT", 13, 25, "ft")),
+		compItem("110", "Trajectory Intent",
+			extItem("TIS", "Trajectory Intent Status", grp(rawEl("NAV", 1), rawEl("NVB", 1), spareEl(5))),
+			repItem("TID", "Trajectory Intent Data",
+				rawEl("TCA", 1), rawEl("NC", 1), rawEl("TCPN", 6), sEl("ALT", 16, 10, "ft"),
+				sEl("LAT", 24, lsbLat23, "deg"), sEl("LON", 24, lsbLat23, "deg"),
+				rawEl("PT", 4), rawEl("TD", 2), rawEl("TRA", 1), rawEl("TOA", 1),
+				uEl("TOV", 24, 1, "s"), uEl("TTR", 16, 0.01, "NM")),
+		),
+		fixedItem("016", "Service Management", uEl("VALUE", 8, 0.5, "s")),
+		fixedItem("008", "Aircraft Operational Status",
+			rawEl("RA", 1), rawEl("TC", 2), rawEl("TS", 1), rawEl("ARV", 1),
+			rawEl("CDTIA", 1), rawEl("NOTTCAS", 1), rawEl("SA", 1)),
+		extItem("271", "Surface Capabilities and Characteristics",
+			grp(spareEl(2), rawEl("POA", 1), rawEl("CDTIS", 1), rawEl("B2LOW", 1), rawEl("RAS", 1), rawEl("IDENT", 1)),
+			grp(rawEl("LW", 4), spareEl(3)),
+		),
+		fixedItem("132", "Message Amplitude", sEl("VALUE", 8, 1, "dBm")),
+		repItem("250", "Mode S MB Data", rawEl("MBDATA", 56), rawEl("BDS1", 4), rawEl("BDS2", 4)),
+		fixedItem("260", "ACAS Resolution Advisory Report",
+			rawEl("TYP", 5), rawEl("STYP", 3), rawEl("ARA", 14), rawEl("RAC", 4),
+			rawEl("RAT", 1), rawEl("MTE", 1), rawEl("TTI", 2), rawEl("TID", 26)),
+		fixedItem("400", "Receiver ID", rawEl("VALUE", 8)),
+		compItem("295", "Data Ages",
+			age("AOS", "Aircraft Operational Status Age"),
+			age("TRD", "Target Report Descriptor Age"),
+			age("M3A", "Mode 3/A Code Age"),
+			age("QI", "Quality Indicators Age"),
+			age("TI", "Trajectory Intent Age"),
+			age("MAM", "Message Amplitude Age"),
+			age("GH", "Geometric Height Age"),
+			age("FL", "Flight Level Age"),
+			age("ISA", "Intermediate State Selected Altitude Age"),
+			age("FSA", "Final State Selected Altitude Age"),
+			age("AS", "Air Speed Age"),
+			age("TAS", "True Air Speed Age"),
+			age("MH", "Magnetic Heading Age"),
+			age("BVR", "Barometric Vertical Rate Age"),
+			age("GVR", "Geometric Vertical Rate Age"),
+			age("GV", "Ground Vector Age"),
+			age("TAR", "Track Angle Rate Age"),
+			age("TID", "Target Identification Age"),
+			age("TS", "Target Status Age"),
+			age("MET", "Met Information Age"),
+			age("ROA", "Roll Angle Age"),
+			age("ARA", "ACAS Resolution Advisory Age"),
+			age("SCC", "Surface Capabilities and Characteristics Age"),
+		),
+		explItem("RE", "Reserved Expansion Field"),
+		explItem("SP", "Special Purpose Field"),
+	)
+}
+
+// CAT048 Monoradar Target Reports, edição 1.21
+func cat048() *Category {
+	return newCategory(48, "1.21",
+		[]string{
+			"010", "140", "020", "040", "070", "090", "130",
+			"220", "240", "250", "161", "042", "200", "170",
+			"210", "030", "080", "100", "110", "120", "230",
+			"260", "055", "050", "065", "060", "SP", "RE",
+		},
+		fixedItem("010", "Data Source Identifier", rawEl("SAC", 8), rawEl("SIC", 8)),
+		fixedItem("140", "Time-of-Day", uEl("VALUE", 24, lsbTime, "s")),
+		extItem("020", "Target Report Descriptor",
+			grp(rawEl("TYP", 3), rawEl("SIM", 1), rawEl("RDP", 1), rawEl("SPI", 1), rawEl("RAB", 1)),
+			grp(rawEl("TST", 1), rawEl("ERR", 1), rawEl("XPP", 1), rawEl("ME", 1), rawEl("MI", 1), rawEl("FOEFRI", 2)),
+			grp(rawEl("ADSB_EP", 1), rawEl("ADSB_VAL", 1), rawEl("SCN_EP", 1), rawEl("SCN_VAL", 1),
+				rawEl("PAI_EP", 1), rawEl("PAI_VAL", 1), spareEl(1)),
+		),
+		fixedItem("040", "Measured Position in Polar Co-ordinates",
+			uEl("RHO", 16, 1.0/256, "NM"), uEl("THETA", 16, lsbAngle, "deg")),
+		fixedItem("070", "Mode-3/A Code",
+			rawEl("V", 1), rawEl("G", 1), rawEl("L", 1), spareEl(1), octEl("MODE3A", 12)),
+		fixedItem("090", "Flight Level", rawEl("V", 1), rawEl("G", 1), sEl("FL", 14, 0.25, "FL")),
+		compItem("130", "Radar Plot Characteristics",
+			fixedItem("SRL", "SSR Plot Runlength", uEl("VALUE", 8, 360.0/(1<<13), "deg")),
+			fixedItem("SRR", "Number of Received Replies", rawEl("VALUE", 8)),
+			fixedItem("SAM", "Amplitude of Received Replies", sEl("VALUE", 8, 1, "dBm")),
+			fixedItem("PRL", "Primary Plot Runlength", uEl("VALUE", 8, 360.0/(1<<13), "deg")),
+			fixedItem("PAM", "Amplitude of Primary Plot", sEl("VALUE", 8, 1, "dBm")),
+			fixedItem("RPD", "Difference in Range", sEl("VALUE", 8, 1.0/256, "NM")),
+			fixedItem("APD", "Difference in Azimuth", sEl("VALUE", 8, 360.0/(1<<14), "deg")),
+		),
+		fixedItem("220", "Aircraft Address", hexEl("VALUE", 24)),
+		fixedItem("240", "Aircraft Identification", strEl("VALUE", 48)),
+		repItem("250", "BDS Register Data", rawEl("MBDATA", 56), rawEl("BDS1", 4), rawEl("BDS2", 4)),
+		fixedItem("161", "Track Number", spareEl(4), rawEl("TRN", 12)),
+		fixedItem("042", "Calculated Position in Cartesian Co-ordinates",
+			sEl("X", 16, 1.0/128, "NM"), sEl("Y", 16, 1.0/128, "NM")),
+		fixedItem("200", "Calculated Track Velocity in Polar Co-ordinates",
+			uEl("GSP", 16, lsbSpeed, "NM/s"), uEl("HDG", 16, lsbAngle, "deg")),
+		extItem("170", "Track Status",
+			grp(rawEl("CNF", 1), rawEl("RAD", 2), rawEl("DOU", 1), rawEl("MAH", 1), rawEl("CDM", 2)),
+			grp(rawEl("TRE", 1), rawEl("GHO", 1), rawEl("SUP", 1), rawEl("TCC", 1), spareEl(3)),
+		),
+		fixedItem("210", "Track Quality",
+			uEl("SIGX", 8, 1.0/128, "NM"), uEl("SIGY", 8, 1.0/128, "NM"),
+			uEl("SIGV", 8, lsbSpeed, "NM/s"), uEl("SIGH", 8, 360.0/(1<<12), "deg")),
+		extItem("030", "Warning/Error Conditions",
+			grp(rawEl("CODE", 7)), grp(rawEl("CODE", 7)), grp(rawEl("CODE", 7)),
+			grp(rawEl("CODE", 7)), grp(rawEl("CODE", 7)), grp(rawEl("CODE", 7)),
+		),
+		fixedItem("080", "Mode-3/A Code Confidence Indicator", spareEl(4), rawEl("CONF", 12)),
+		fixedItem("100", "Mode-C Code and Confidence Indicator",
+			rawEl("V", 1), rawEl("G", 1), spareEl(2), rawEl("MODEC", 12), spareEl(4), rawEl("QC", 12)),
+		fixedItem("110", "Height Measured by 3D Radar", spareEl(2), sEl("VALUE", 14, 25, "ft")),
+		compItem("120", "Radial Doppler Speed",
+			fixedItem("CAL", "Calculated Doppler Speed", rawEl("D", 1), spareEl(5), sEl("VALUE", 10, 1, "m/s")),
+			repItem("RDS", "Raw Doppler Speed", rawEl("DOP", 16), rawEl("AMB", 16), rawEl("FRQ", 16)),
+		),
+		fixedItem("230", "Communications/ACAS Capability and Flight Status",
+			rawEl("COM", 3), rawEl("STAT", 3), rawEl("SI", 1), spareEl(1), rawEl("MSSC", 1),
+			rawEl("ARC", 1), rawEl("AIC", 1), rawEl("B1A", 1), rawEl("B1B", 4)),
+		fixedItem("260", "ACAS Resolution Advisory Report", rawEl("VALUE", 56)),
+		fixedItem("055", "Mode-1 Code", rawEl("V", 1), rawEl("G", 1), rawEl("L", 1), rawEl("MODE1", 5)),
+		fixedItem("050", "Mode-2 Code",
+			rawEl("V", 1), rawEl("G", 1), rawEl("L", 1), spareEl(1), octEl("MODE2", 12)),
+		fixedItem("065", "Mode-1 Code Confidence Indicator", spareEl(3), rawEl("CONF", 5)),
+		fixedItem("060", "Mode-2 Code Confidence Indicator", spareEl(4), rawEl("CONF", 12)),
+		explItem("SP", "Special Purpose Field"),
+		explItem("RE", "Reserved Expansion Field"),
+	)
+}